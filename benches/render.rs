@@ -0,0 +1,50 @@
+//! Times drawing a frame of a 200x100 board, with `cargo bench --bench render`
+//!
+//! The drawing code lives in the binary, so its modules are included from there
+
+#[allow(dead_code, unused_imports)]
+#[path = "../src/consts.rs"]
+mod consts;
+#[allow(dead_code, unused_imports)]
+#[path = "../src/render.rs"]
+mod render;
+#[allow(dead_code, unused_imports)]
+#[path = "../src/theme.rs"]
+mod theme;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use snake::{Board, Game, Position, Snake, Team};
+use tui::{backend::TestBackend, Terminal};
+
+use render::{BoardView, View};
+use theme::Theme;
+
+const SIZE: (usize, usize) = (200, 100);
+
+/// Used to get a game on a board of the size, with a snake that has grown for a while
+fn make_game() -> Game {
+    let team = Team::new(0, true).unwrap();
+    let player = Snake::new(Position::new(SIZE.0 as isize / 2, SIZE.1 as isize / 2), &team);
+    let mut game = Game::seeded(Board::new(SIZE.0, SIZE.1, false), vec![player], vec![team], 0).with_fruits(vec![20]);
+    for _ in 0..60 {
+        game.players_mut()[0].eat();
+        game.tick();
+    }
+    game
+}
+
+fn frame(c: &mut Criterion) {
+    let game = make_game();
+    let theme = Theme::default();
+    let mut terminal = Terminal::new(TestBackend::new(2 * SIZE.0 as u16, SIZE.1 as u16)).unwrap();
+    c.bench_function("frame 200x100", |b| {
+        b.iter(|| {
+            terminal
+                .draw(|f| f.render_widget(BoardView::new(&game, &theme, View::whole(SIZE)), f.size()))
+                .unwrap();
+        })
+    });
+}
+
+criterion_group!(benches, frame);
+criterion_main!(benches);
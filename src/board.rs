@@ -1,12 +1,7 @@
-use tui::style::Color;
-use tui::{
-text::Span,
-style::Style,
-};
-
+use crate::controller::helper_enums::Directions;
 use crate::snake::Position;
+use crate::terrain::Terrain;
 use crate::Items;
-use crate::consts::*;
 
 use std::ops::{
     Index,
@@ -14,49 +9,60 @@ use std::ops::{
 };
 
 pub const DEFAULT: usize = 16;
+/// The smallest size a board can have, and still have room inside the walls
+pub const MIN_SIZE: usize = 5;
+/// The largest size a board can have
+pub const MAX_SIZE: usize = 1000;
+/// The smallest size a board with gaps can have, so the corners stay walls
+pub const MIN_GAPS_SIZE: usize = 7;
 
 /// Contains the entire board, with interactions with it
-pub struct Board<'a> {
-    board: Vec<Vec<(Items, Span<'a>)>>,
+pub struct Board {
+    board: Vec<Vec<Items>>,
     max_x: usize,
-    max_y: usize
+    max_y: usize,
+    /// The pairs of cells that lead to each other
+    portals: Vec<(Position, Position)>,
+    /// The ground under every cell
+    terrain: Vec<Vec<Terrain>>,
+    /// The corners of the paths walls move along, starting at the first
+    paths: Vec<Vec<Position>>,
 }
 
 /// Used to get a board where there are no gaps in the walls
-fn board_ngates<'a>(size_x: usize, size_y: usize) -> Vec<Vec<(Items, Span<'a>)>>  {
-    let mut board = vec![vec![(Items::EMPTY, Span::from(EMPTY)); size_x]; size_y];
-    for y_pos in 0..board.len() {
-        for x_pos in 0..board[y_pos].len() {
-            if x_pos == (size_x - 1) || x_pos == 0 {
-                board[y_pos][x_pos] = (Items::WALL, Span::styled(WALL, Style::default().bg(Color::White)));
-            } else if y_pos == (size_y-1) || y_pos == 0 {
-                board[y_pos][x_pos] = (Items::WALL, Span::styled(WALL, Style::default().bg(Color::White)));
+fn board_ngates(size_x: usize, size_y: usize) -> Vec<Vec<Items>> {
+    let mut board = vec![vec![Items::EMPTY; size_x]; size_y];
+    for (y_pos, row) in board.iter_mut().enumerate() {
+        for (x_pos, cell) in row.iter_mut().enumerate() {
+            if x_pos == (size_x - 1) || x_pos == 0 || y_pos == (size_y - 1) || y_pos == 0 {
+                *cell = Items::WALL;
             }
-        } 
+        }
     }
     board
 }
 
 /// Used to get a board where there are gaps in the walls
-fn board_gates<'a>(size_x: usize, size_y: usize) -> Vec<Vec<(Items, Span<'a>)>> {
-    let mut board = vec![vec![(Items::EMPTY, Span::from(EMPTY)); size_x]; size_y];
-    for y_pos in 0..board.len() {
-        for x_pos in 0..board[y_pos].len() {
-            if x_pos == (size_x - 1) || x_pos == 0 {
-                if y_pos > ((size_y/2) + 2) || y_pos < ((size_y/2) - 2) {
-                    board[y_pos][x_pos] = (Items::WALL, Span::styled(WALL, Style::default().bg(Color::White)));
-                }
+fn board_gates(size_x: usize, size_y: usize) -> Vec<Vec<Items>> {
+    let mut board = vec![vec![Items::EMPTY; size_x]; size_y];
+    for (y_pos, row) in board.iter_mut().enumerate() {
+        for (x_pos, cell) in row.iter_mut().enumerate() {
+            let wall = if x_pos == (size_x - 1) || x_pos == 0 {
+                y_pos > ((size_y/2) + 2) || y_pos < ((size_y/2) - 2)
             } else if y_pos == (size_y-1) || y_pos == 0 {
-                if x_pos > ((size_x/2) + 2) || x_pos < ((size_x/2) - 2) {
-                    board[y_pos][x_pos] = (Items::WALL, Span::styled(WALL, Style::default().bg(Color::White)));
-                }
+                x_pos > ((size_x/2) + 2) || x_pos < ((size_x/2) - 2)
+            } else {
+                false
+            };
+            if wall {
+                *cell = Items::WALL;
             }
         }
     }
     board
 }
 
-impl<'a> Board<'a> {
+impl Board {
     /// Creates a new boad, and populates it
     pub fn new(size_x: usize, size_y: usize, gates: bool) -> Self {
         let board = if gates {
@@ -64,50 +70,131 @@ impl<'a> Board<'a> {
         } else {
             board_ngates(size_x, size_y)
         };
+        Self::from_vec(board)
+    }
+
+    /// Creates a new board from its rows, which all have to be as long
+    pub fn from_vec(board: Vec<Vec<Items>>) -> Self {
+        let (max_x, max_y) = (board.first().map_or(0, Vec::len), board.len());
         Self {
+            max_x,
+            max_y,
             board,
-            max_x: size_x,
-            max_y: size_y
+            portals: vec![],
+            terrain: vec![vec![Terrain::Plain; max_x]; max_y],
+            paths: vec![],
         }
     }
 
-    pub fn get_max_size(&self) -> (usize, usize) {
-        (self.max_x, self.max_y)
+    /// Used to add portals between pairs of cells, which are no longer walls
+    /// Pairs with an end outside the board are left out
+    pub fn with_portals(mut self, portals: Vec<(Position, Position)>) -> Self {
+        let portals: Vec<_> = portals
+            .into_iter()
+            .filter(|(a, b)| self.contains(a) && self.contains(b))
+            .collect();
+        for (a, b) in &portals {
+            self[a] = Items::PORTAL;
+            self[b] = Items::PORTAL;
+        }
+        self.portals = portals;
+        self
     }
 
-    /// Checks if a position is empty
-    pub fn check_position(&self, pos: &Position, ident: &Items) -> bool {
-        self[pos].0 == *ident
+    pub fn portals(&self) -> &[(Position, Position)] {
+        &self.portals
     }
 
-    /// Changes a position to another if it is not a wall
-    /// Returns true if the position changes, else false
-    pub fn change_position(&mut self, pos: &Position, item: Items) -> bool {
-        return if !(self.check_position(pos, &Items::WALL) || self.check_position(pos, &Items::SNAKE)) {
-        let snake = match item {
-            Items::SNAKE => (Span::styled(SNEK, Style::default().bg(Color::Green))),
-            Items::OSNAKE => (Span::styled(SNEK, Style::default().bg(Color::Yellow))),
-            _ => Span::from(EMPTY),
-        };
-            self[pos] = (item, snake);
-            true
-        } else {
-            false
+    /// Used to add walls that move along paths, given by their corners, which start at the first corner
+    /// Paths with a corner outside the board are left out
+    pub fn with_paths(mut self, paths: Vec<Vec<Position>>) -> Self {
+        let paths: Vec<_> = paths
+            .into_iter()
+            .filter(|path| !path.is_empty() && path.iter().all(|pos| self.contains(pos)))
+            .collect();
+        for path in &paths {
+            self[&path[0]] = Items::WALL;
         }
+        self.paths = paths;
+        self
     }
 
-    pub fn remove_position(&mut self, pos: &Position) -> bool {
-        return if !self.check_position(pos, &Items::WALL){
-            self[pos] = (Items::EMPTY, Span::from(EMPTY));
-            true
-        } else {
-            false
+    pub fn paths(&self) -> &[Vec<Position>] {
+        &self.paths
+    }
+
+    /// Used to lay terrain on cells
+    /// Cells outside the board are left out
+    pub fn with_terrain(mut self, cells: Vec<(Position, Terrain)>) -> Self {
+        for (pos, terrain) in cells {
+            if self.contains(&pos) {
+                self.terrain[pos.y as usize][pos.x as usize] = terrain;
+            }
+        }
+        self
+    }
+
+    /// Used to check if a position is on the board
+    pub fn contains(&self, pos: &Position) -> bool {
+        (0..self.max_x as isize).contains(&pos.x) && (0..self.max_y as isize).contains(&pos.y)
+    }
+
+    /// Used to get the ground under a position
+    pub fn terrain(&self, pos: &Position) -> &Terrain {
+        &self.terrain[pos.y as usize][pos.x as usize]
+    }
+
+    /// Used to get every cell that has terrain other than plain ground
+    pub fn terrain_cells(&self) -> impl Iterator<Item = (Position, &Terrain)> + '_ {
+        self.terrain.iter().enumerate().flat_map(|(y, row)| {
+            row.iter()
+                .enumerate()
+                .filter(|(_, terrain)| **terrain != Terrain::Plain)
+                .map(move |(x, terrain)| (Position::new(x as isize, y as isize), terrain))
+        })
+    }
+
+    /// Used to get the other end of a portal, if there is a portal on the position
+    pub fn portal(&self, pos: &Position) -> Option<&Position> {
+        self.portals.iter().find_map(|(a, b)| {
+            if a == pos {
+                Some(b)
+            } else if b == pos {
+                Some(a)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Used to get where a step in a direction from a position ends up,
+    /// going over the edges, and out on the other side of portals
+    ///
+    /// A portal that comes out onto another portal leads on through that one as well,
+    /// until every portal has been gone through once
+    pub fn next_pos(&self, pos: &Position, dirr: &Directions) -> Position {
+        let mut next = self.get_overflow_pos(pos.step(dirr));
+        for _ in 0..self.portals.len() {
+            let Some(exit) = self.portal(&next) else {
+                break;
+            };
+            next = self.get_overflow_pos(exit.step(dirr));
         }
+        next
+    }
+
+    pub fn get_max_size(&self) -> (usize, usize) {
+        (self.max_x, self.max_y)
+    }
+
+    /// Checks if a position is empty
+    pub fn check_position(&self, pos: &Position, ident: &Items) -> bool {
+        self[pos] == *ident
     }
 
     /// Used to get a position that overflows the board
     pub fn get_overflow_pos(&self, pos: Position) -> Position {
-        return if pos.x == (self.max_x as isize) {
+        if pos.x == (self.max_x as isize) {
             Position::new(0, pos.y)
         } else if pos.y == (self.max_y as isize) {
             Position::new(pos.x, 0)
@@ -120,41 +207,62 @@ impl<'a> Board<'a> {
         }
     }
 
+    /// Used to find every cell that can be reached from the positions without going through a wall,
+    /// or against an arrow
+    /// Returns a grid of the board, where the reachable cells are true
+    pub fn reachable(&self, from: &[Position]) -> Vec<Vec<bool>> {
+        let mut reached = vec![vec![false; self.max_x]; self.max_y];
+        let mut stack: Vec<Position> = from.to_vec();
+        while let Some(pos) = stack.pop() {
+            if reached[pos.y as usize][pos.x as usize] || self.check_position(&pos, &Items::WALL) {
+                continue;
+            }
+            reached[pos.y as usize][pos.x as usize] = true;
+            for dirr in [Directions::UP, Directions::DOWN, Directions::LEFT, Directions::RIGHT] {
+                let next = self.next_pos(&pos, &dirr);
+                if self.terrain(&next).allows(&dirr) {
+                    stack.push(next);
+                }
+            }
+        }
+        reached
+    }
+
     /// Returns the underlying vectors
-    pub fn get_vec(&self) -> &Vec<Vec<(Items, Span<'a>)>> {
+    pub fn get_vec(&self) -> &Vec<Vec<Items>> {
         &self.board
     } 
 }
 
-impl<'a> Default for Board<'a> {
+impl Default for Board {
     fn default() -> Self {
         Self::new(DEFAULT, DEFAULT, false)
     }
 }
 
-impl<'a> Index<&Position> for Board<'a> {
-    type Output = (Items, Span<'a>);
+impl Index<&Position> for Board {
+    type Output = Items;
 
     fn index(&self, index: &Position) -> &Self::Output {
         &self.board[index.y as usize][index.x as usize]
     }
 }
 
-impl<'a> IndexMut<&Position> for Board<'a> {
+impl IndexMut<&Position> for Board {
     fn index_mut(&mut self, index: &Position) -> &mut Self::Output {
         &mut self.board[index.y as usize][index.x as usize]
     }
 }
 
-impl<'a> Index<Position> for Board<'a> {
-    type Output = (Items, Span<'a>);
+impl Index<Position> for Board {
+    type Output = Items;
 
     fn index(&self, index: Position) -> &Self::Output {
         &self.board[index.y as usize][index.x as usize]
     }
 }
 
-impl<'a> IndexMut<Position> for Board<'a> {
+impl IndexMut<Position> for Board {
     fn index_mut(&mut self, index: Position) -> &mut Self::Output {
         &mut self.board[index.y as usize][index.x as usize]
     }
@@ -181,19 +289,67 @@ mod board_test {
         let mut board = get_board();
         let pos = Position::new(1, 1);
         assert!(board.check_position(&pos, &Items::EMPTY));
-        assert!(board.change_position(&pos, Items::FRUIT));
+        board[&pos] = Items::FRUIT;
         assert!(board.check_position(&pos, &Items::FRUIT));
+        board[&pos] = Items::EMPTY;
+        assert!(board.check_position(&pos, &Items::EMPTY));
+    }
 
-        let mut board = get_board();
-        let pos = Position::new(0, 0);
-        assert!(board.check_position(&pos, &Items::WALL));
-        assert_eq!(false, board.change_position(&pos, Items::EMPTY));
-        assert!(board.check_position(&pos, &Items::WALL));
+    #[test]
+    fn test_portals() {
+        let board = get_board().with_portals(vec![(Position::new(2, 2), Position::new(5, 5))]);
+        assert!(board.check_position(&Position::new(5, 5), &Items::PORTAL));
+        assert_eq!(Some(&Position::new(2, 2)), board.portal(&Position::new(5, 5)));
+        assert_eq!(Position::new(5, 4), board.next_pos(&Position::new(2, 3), &Directions::UP));
+        assert_eq!(Position::new(1, 2), board.next_pos(&Position::new(6, 5), &Directions::LEFT));
+        assert_eq!(Position::new(3, 3), board.next_pos(&Position::new(2, 3), &Directions::RIGHT));
+
+        // Out of one portal and straight into the next
+        let board = get_board().with_portals(vec![
+            (Position::new(2, 2), Position::new(5, 5)),
+            (Position::new(6, 5), Position::new(2, 4)),
+        ]);
+        assert_eq!(Position::new(3, 4), board.next_pos(&Position::new(1, 2), &Directions::RIGHT));
+        assert_eq!(Position::new(1, 2), board.next_pos(&Position::new(3, 4), &Directions::LEFT));
+
+        // Ends outside the board are left out
+        let board = get_board().with_portals(vec![(Position::new(2, 2), Position::new(8, 5))]);
+        assert!(board.portals().is_empty());
+        assert!(board.check_position(&Position::new(2, 2), &Items::EMPTY));
+    }
 
+    #[test]
+    fn test_reachable() {
         let mut board = get_board();
-        let pos = Position::new(1, 1);
-        assert!(board.check_position(&pos, &Items::EMPTY));
-        assert!(board.change_position(&pos, Items::SNAKE));
-        assert!(board.check_position(&pos, &Items::SNAKE));
+        for y in 0..8 {
+            board[Position::new(4, y)] = Items::WALL;
+        }
+        let reached = board.reachable(&[Position::new(1, 1)]);
+        assert!(reached[6][3]);
+        assert!(!reached[1][5]);
+        assert!(!reached[0][0]);
+
+        // Through the gaps in the walls, to the other side
+        let mut board = Board::new(8, 8, true);
+        for y in 1..7 {
+            board[Position::new(4, y)] = Items::WALL;
+        }
+        assert!(board.reachable(&[Position::new(1, 1)])[1][5]);
+
+        // Only the way the arrows point
+        let arrows = (1..7).map(|y| (Position::new(4, y), Terrain::Arrow(Directions::RIGHT))).collect();
+        let board = get_board().with_terrain(arrows);
+        assert!(board.reachable(&[Position::new(1, 1)])[1][5]);
+        assert!(!board.reachable(&[Position::new(6, 1)])[1][1]);
+    }
+
+    #[test]
+    fn test_terrain() {
+        let board = get_board().with_terrain(vec![
+            (Position::new(2, 2), Terrain::Mud),
+            (Position::new(8, 2), Terrain::Ice),
+            (Position::new(2, -1), Terrain::Boost),
+        ]);
+        assert_eq!(vec![(Position::new(2, 2), &Terrain::Mud)], board.terrain_cells().collect::<Vec<_>>());
     }
 }
@@ -0,0 +1,320 @@
+use std::{
+    fmt::{self, Display, Write as _},
+    io::{self, BufRead, BufReader, Write},
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    thread,
+    time::Duration,
+};
+
+use rand::{prelude::StdRng, seq::SliceRandom, SeedableRng};
+
+use crate::{
+    controller::helper_enums::Directions,
+    game::Game,
+    snake::{opposite, Position},
+    terrain::Terrain,
+    Items,
+};
+
+/// The names of the bots that are built into the game
+pub const BUILTIN: [&str; 2] = ["greedy", "random"];
+
+const DIRECTIONS: [Directions; 4] = [
+    Directions::UP,
+    Directions::DOWN,
+    Directions::LEFT,
+    Directions::RIGHT,
+];
+
+/// Something that can decide where a snake goes next
+pub trait Bot {
+    /// Decides the next direction of the snake at index me
+    fn next_move(&mut self, game: &Game, me: usize) -> Result<Directions, BotError>;
+}
+
+/// The reasons a bot can be disqualified
+#[derive(Debug, PartialEq)]
+pub enum BotError {
+    Timeout,
+    Invalid(String),
+    Closed,
+}
+
+impl Display for BotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BotError::Timeout => write!(f, "the bot did not answer in time"),
+            BotError::Invalid(line) => write!(f, "the bot answered with invalid output: {:?}", line),
+            BotError::Closed => write!(f, "the bot closed its connection"),
+        }
+    }
+}
+
+/// Used to get one of the built-in bots by name
+///
+/// seed decides the choices made by bots using randomness
+pub fn builtin(name: &str, seed: u64) -> Option<Box<dyn Bot + Send>> {
+    match name {
+        "greedy" => Some(Box::new(GreedyBot)),
+        "random" => Some(Box::new(RandomBot::new(seed))),
+        _ => None,
+    }
+}
+
+/// A bot that goes straight for the closest fruit it can eat, while staying alive
+pub struct GreedyBot;
+
+impl Bot for GreedyBot {
+    fn next_move(&mut self, game: &Game, me: usize) -> Result<Directions, BotError> {
+        let player = &game.players()[me];
+        let distance = |pos: &Position| {
+            game.fruits()
+                .iter()
+                .filter(|(_, fruit)| fruit == player.fruit() || *fruit == Items::FOOD)
+                .map(|(fruit_pos, _)| (fruit_pos.x - pos.x).abs() + (fruit_pos.y - pos.y).abs())
+                .min()
+                .unwrap_or(0)
+        };
+        Ok(safe_moves(game, me)
+            .into_iter()
+            .min_by_key(|(_, pos)| distance(pos))
+            .map(|(dirr, _)| dirr)
+            .unwrap_or_else(|| player.get_dirr()))
+    }
+}
+
+/// A bot that moves at random, while staying alive
+pub struct RandomBot {
+    rng: StdRng,
+}
+
+impl RandomBot {
+    pub fn new(seed: u64) -> Self {
+        RandomBot {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Bot for RandomBot {
+    fn next_move(&mut self, game: &Game, me: usize) -> Result<Directions, BotError> {
+        Ok(safe_moves(game, me)
+            .choose(&mut self.rng)
+            .map(|(dirr, _)| dirr.clone())
+            .unwrap_or_else(|| game.players()[me].get_dirr()))
+    }
+}
+
+/// Used to get the directions the snake at index me can move in without dying right away,
+/// together with the position it would end up on
+fn safe_moves(game: &Game, me: usize) -> Vec<(Directions, Position)> {
+    let player = &game.players()[me];
+    let board = game.board();
+    DIRECTIONS
+        .iter()
+        .filter(|dirr| **dirr != opposite(&player.get_dirr()))
+        .map(|dirr| (dirr.clone(), board.next_pos(&player.get_pos(), dirr)))
+        .filter(|(dirr, pos)| {
+            (board.check_position(pos, &Items::EMPTY)
+                || board.check_position(pos, player.fruit())
+                || board.check_position(pos, &Items::FOOD))
+                && game.occupancy().is_empty(pos)
+                && board.terrain(pos).allows(dirr)
+        })
+        .collect()
+}
+
+/// A bot running as another process, talking the line protocol
+pub struct ExternalBot {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+    timeout: Duration,
+}
+
+impl ExternalBot {
+    /// Starts a new [`ExternalBot`]
+    ///
+    /// command is the program to run, followed by its arguments, separated by whitespace
+    /// timeout is how long the bot has to answer every move
+    pub fn spawn(command: &str, timeout: Duration) -> io::Result<Self> {
+        let mut parts = command.split_whitespace();
+        let program = parts
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "empty bot command"))?;
+        let mut child = Command::new(program)
+            .args(parts)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+
+        // Reading is done on its own thread, so that a silent bot can time out
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(ExternalBot {
+            child,
+            stdin,
+            lines,
+            timeout,
+        })
+    }
+}
+
+impl Bot for ExternalBot {
+    fn next_move(&mut self, game: &Game, me: usize) -> Result<Directions, BotError> {
+        self.stdin
+            .write_all(encode(game, me).as_bytes())
+            .and_then(|_| self.stdin.flush())
+            .map_err(|_| BotError::Closed)?;
+        match self.lines.recv_timeout(self.timeout) {
+            Ok(line) => parse_direction(&line).ok_or(BotError::Invalid(line)),
+            Err(RecvTimeoutError::Timeout) => Err(BotError::Timeout),
+            Err(RecvTimeoutError::Disconnected) => Err(BotError::Closed),
+        }
+    }
+}
+
+impl Drop for ExternalBot {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Used to get the character used for a cell in the protocol
+fn cell_char(item: &Items) -> char {
+    match item {
+        Items::WALL => '#',
+        Items::EMPTY => '.',
+        Items::SNAKE => 'S',
+        Items::OSNAKE => 'O',
+        Items::FRUIT => 'F',
+        Items::OFRUIT => 'G',
+        Items::PORTAL => 'P',
+        Items::FOOD => 'X',
+    }
+}
+
+fn direction_name(dirr: &Directions) -> &'static str {
+    match dirr {
+        Directions::UP => "UP",
+        Directions::DOWN => "DOWN",
+        Directions::LEFT => "LEFT",
+        Directions::RIGHT => "RIGHT",
+    }
+}
+
+/// Used to get the name of a terrain, where an arrow is named by its direction
+fn terrain_name(terrain: &Terrain) -> &'static str {
+    match terrain {
+        Terrain::Plain => "PLAIN",
+        Terrain::Mud => "MUD",
+        Terrain::Boost => "BOOST",
+        Terrain::Ice => "ICE",
+        Terrain::Arrow(dirr) => direction_name(dirr),
+    }
+}
+
+/// Used to parse the answer of a bot
+pub fn parse_direction(line: &str) -> Option<Directions> {
+    match line.trim().to_ascii_uppercase().as_str() {
+        "UP" => Some(Directions::UP),
+        "DOWN" => Some(Directions::DOWN),
+        "LEFT" => Some(Directions::LEFT),
+        "RIGHT" => Some(Directions::RIGHT),
+        _ => None,
+    }
+}
+
+/// Used to write the state of the game in the line protocol, as seen by the snake at index me
+pub fn encode(game: &Game, me: usize) -> String {
+    let (width, height) = game.board().get_max_size();
+    let mut out = String::new();
+    writeln!(out, "tick {}", game.ticks()).unwrap();
+    writeln!(out, "you {}", me).unwrap();
+    writeln!(out, "board {} {}", width, height).unwrap();
+
+    for row in game.cells() {
+        out.extend(row.iter().map(cell_char));
+        out.push('\n');
+    }
+
+    writeln!(out, "snakes {}", game.players().len()).unwrap();
+    for (index, player) in game.players().iter().enumerate() {
+        write!(
+            out,
+            "{} {} {} {}",
+            index,
+            player.get_team(),
+            direction_name(&player.get_dirr()),
+            player.get_size()
+        )
+        .unwrap();
+        for pos in player.get_tail() {
+            write!(out, " {},{}", pos.x, pos.y).unwrap();
+        }
+        out.push('\n');
+    }
+
+    writeln!(out, "fruits {}", game.fruits().len()).unwrap();
+    for (pos, fruit) in game.fruits() {
+        writeln!(out, "{} {} {}", pos.x, pos.y, cell_char(fruit)).unwrap();
+    }
+    writeln!(out, "portals {}", game.board().portals().len()).unwrap();
+    for (a, b) in game.board().portals() {
+        writeln!(out, "{} {} {} {}", a.x, a.y, b.x, b.y).unwrap();
+    }
+    let terrain: Vec<_> = game.board().terrain_cells().collect();
+    writeln!(out, "terrain {}", terrain.len()).unwrap();
+    for (pos, terrain) in terrain {
+        writeln!(out, "{} {} {}", pos.x, pos.y, terrain_name(terrain)).unwrap();
+    }
+    out.push_str("end\n");
+    out
+}
+
+#[cfg(test)]
+mod bot_test {
+    use crate::bot::*;
+    use crate::{board::Board, snake::Snake, team::Team};
+
+    #[test]
+    fn test_parse_direction() {
+        assert_eq!(Some(Directions::UP), parse_direction("UP\n"));
+        assert_eq!(Some(Directions::LEFT), parse_direction(" left "));
+        assert_eq!(None, parse_direction("NORTH"));
+        assert_eq!(None, parse_direction(""));
+    }
+
+    #[test]
+    fn test_greedy_avoids_walls() {
+        let team = Team::new(0, true).unwrap();
+        let snake = Snake::new(Position::new(1, 4), &team);
+        let game = Game::new(Board::new(8, 8, false), vec![snake], vec![team]);
+        let dirr = GreedyBot.next_move(&game, 0).unwrap();
+        assert!(dirr == Directions::UP || dirr == Directions::DOWN);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_invalid_output() {
+        let team = Team::new(0, true).unwrap();
+        let snake = Snake::new(Position::new(4, 4), &team);
+        let game = Game::new(Board::new(8, 8, false), vec![snake], vec![team]);
+
+        // cat answers with the first line it was given, which is not a direction
+        let mut bot = ExternalBot::spawn("cat", Duration::from_secs(5)).unwrap();
+        assert_eq!(Err(BotError::Invalid("tick 0".to_string())), bot.next_move(&game, 0));
+    }
+}
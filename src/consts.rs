@@ -1,4 +1,13 @@
 pub const WALL: &str = " W";
-pub const FRUIT: &str = " %";
+pub const FRUITS: [&str; 2] = [" %", " *"];
 pub const SNEK: &str = " S";
+pub const HEADS: [&str; 2] = [" @", " Q"];
+pub const TAIL: &str = " s";
 pub const EMPTY: &str = "  ";
+pub const WARNING: &str = " !";
+pub const PORTAL: &str = " O";
+pub const MUD: &str = " ~";
+pub const BOOST: &str = " +";
+pub const ICE: &str = " -";
+pub const ARROWS: [&str; 4] = [" ^", " v", " <", " >"];
+pub const FOOD: &str = " o";
@@ -1,10 +1,14 @@
+use serde::Serialize;
+
+#[allow(dead_code)]
 pub enum MoveOpt<T> {
     Some(T),
     Same,
     None,
 }
 
-#[derive(PartialEq, PartialOrd, Clone)]
+#[allow(clippy::upper_case_acronyms)]
+#[derive(PartialEq, PartialOrd, Clone, Debug, Serialize)]
 pub enum Directions {
     UP,
     DOWN,
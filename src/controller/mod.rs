@@ -1,9 +1,21 @@
 pub mod helper_enums;
 
+#[cfg(feature = "terminal")]
 use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
 
+#[cfg(feature = "terminal")]
 use self::helper_enums::{Directions, MoveOpt};
 
+/// Used to turn a key press into the direction of a player
+#[cfg(feature = "terminal")]
+pub type Keys = fn(Event) -> MoveOpt<Directions>;
+
+/// The keys of player one, as shown to the players
+pub const PLAYER_ONE_KEYS: &str = "arrow keys";
+/// The keys of player two, as shown to the players
+pub const PLAYER_TWO_KEYS: &str = "w a s d";
+
+#[cfg(feature = "terminal")]
 pub fn get_player_one(input: Event) -> MoveOpt<Directions> {
     match input {
         Event::Key(KeyEvent {
@@ -26,6 +38,7 @@ pub fn get_player_one(input: Event) -> MoveOpt<Directions> {
     }
 }
 
+#[cfg(feature = "terminal")]
 pub fn get_player_two(input: Event) -> MoveOpt<Directions> {
     match input {
         Event::Key(KeyEvent {
@@ -0,0 +1,366 @@
+use crate::{
+    controller::helper_enums::Directions,
+    game::Game,
+    level::Level,
+    snake::{Position, Snake},
+    team::Team,
+    terrain::Terrain,
+    Items,
+};
+
+/// The reward for eating a fruit
+pub const FRUIT_REWARD: f32 = 1.0;
+/// The reward for dying
+pub const DEATH_REWARD: f32 = -1.0;
+/// The reward for filling the board, on top of the reward for the last fruit
+pub const FULL_REWARD: f32 = 10.0;
+/// The number of terrain channels: mud, boost and ice, and arrows pointing up, down, left and right,
+/// where up is forward in the egocentric encoding
+pub const TERRAIN_CHANNELS: usize = 7;
+
+/// How the game is turned into numbers for an agent
+#[derive(Clone, Debug, PartialEq)]
+pub enum Encoding {
+    /// The whole board, as the channels walls, body, head, fruit and portals, each of the size of the board,
+    /// followed by the terrain channels
+    Grid,
+    /// A square around the head with the given radius, turned so the snake always faces up,
+    /// as the channels obstacles, fruit and portals followed by the terrain channels,
+    /// which goes on at the other side past the edges of the board
+    Egocentric(usize),
+    /// Danger straight ahead, to the left and to the right, the current direction,
+    /// where the nearest fruit is, and the length of the snake
+    Features,
+}
+
+/// What an agent sees of the game
+#[derive(Clone, Debug, PartialEq)]
+pub struct Observation {
+    /// The size of every dimension of the data, outermost first
+    pub shape: Vec<usize>,
+    pub data: Vec<f32>,
+}
+
+/// An environment for training agents on the game, with the same rules as a normal game
+pub struct SnakeEnv {
+    level: Level,
+    encoding: Encoding,
+    max_ticks: usize,
+    game: Option<Game>,
+}
+
+impl SnakeEnv {
+    /// Creates a new [`SnakeEnv`], which has to be reset before it is used
+    ///
+    /// max_ticks is the number of steps before an episode ends, even if the snake is alive
+    pub fn new(level: Level, encoding: Encoding, max_ticks: usize) -> Self {
+        SnakeEnv {
+            level,
+            encoding,
+            max_ticks,
+            game: None,
+        }
+    }
+
+    /// Starts a new episode, with fruit placed the same way every time for the same seed
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        let team = Team::all(true).remove(0);
+        let snake = Snake::new(self.level.spawn_position(0, 1), &team);
+        self.game = Some(Game::seeded(self.level.board(), vec![snake], vec![team], seed));
+        self.observe()
+    }
+
+    /// Moves the snake one step in the direction of the action
+    /// Returns what the agent sees afterwards, the reward, and if the episode is over
+    pub fn step(&mut self, action: Directions) -> (Observation, f32, bool) {
+        let game = self.game.as_mut().expect("reset has to be called before step");
+        let eaten = game.players()[0].eaten();
+        game.players_mut()[0].turn(action);
+
+        let going = game.tick();
+        let reward = if !game.players()[0].is_alive() {
+            DEATH_REWARD
+        } else {
+            let full = if game.is_full() { FULL_REWARD } else { 0.0 };
+            (game.players()[0].eaten() - eaten) as f32 * FRUIT_REWARD + full
+        };
+        let done = !going || game.ticks() >= self.max_ticks;
+        (self.observe(), reward, done)
+    }
+
+    pub fn game(&self) -> Option<&Game> {
+        self.game.as_ref()
+    }
+
+    /// Used to get the current observation, in the encoding of the environment
+    pub fn observe(&self) -> Observation {
+        let game = self.game.as_ref().expect("reset has to be called before observe");
+        match self.encoding {
+            Encoding::Grid => grid(game),
+            Encoding::Egocentric(radius) => egocentric(game, radius),
+            Encoding::Features => features(game),
+        }
+    }
+}
+
+/// Used to check if moving onto a position would kill the snake
+fn is_obstacle(game: &Game, pos: &Position) -> bool {
+    game.board().check_position(pos, &Items::WALL)
+        || !game.occupancy().is_empty(pos)
+}
+
+/// Used to bring a position outside the board back onto it, on the other side,
+/// the same way a snake going through a gap in the walls does
+fn wrap(game: &Game, pos: Position) -> Position {
+    let (max_x, max_y) = game.board().get_max_size();
+    Position::new(pos.x.rem_euclid(max_x as isize), pos.y.rem_euclid(max_y as isize))
+}
+
+fn is_fruit(game: &Game, pos: &Position) -> bool {
+    game.board().check_position(pos, game.players()[0].fruit())
+}
+
+/// Used to get the terrain channel a cell belongs to, for a snake heading in the direction, if any
+fn terrain_channel(terrain: &Terrain, dirr: &Directions) -> Option<usize> {
+    match terrain {
+        Terrain::Plain => None,
+        Terrain::Mud => Some(0),
+        Terrain::Boost => Some(1),
+        Terrain::Ice => Some(2),
+        Terrain::Arrow(arrow) => [(0, -1), (0, 1), (-1, 0), (1, 0)]
+            .iter()
+            .position(|(x, y)| turned(dirr, *x, *y) == *arrow)
+            .map(|channel| 3 + channel),
+    }
+}
+
+fn grid(game: &Game) -> Observation {
+    let (max_x, max_y) = game.board().get_max_size();
+    let size = max_x * max_y;
+    let mut data = vec![0.0; (5 + TERRAIN_CHANNELS) * size];
+    let index = |channel: usize, pos: &Position| channel * size + pos.y as usize * max_x + pos.x as usize;
+
+    for (y_pos, row) in game.board().get_vec().iter().enumerate() {
+        for (x_pos, cell) in row.iter().enumerate() {
+            let pos = Position::new(x_pos as isize, y_pos as isize);
+            match cell {
+                Items::WALL => data[index(0, &pos)] = 1.0,
+                Items::PORTAL => data[index(4, &pos)] = 1.0,
+                _ => (),
+            }
+        }
+    }
+    let player = &game.players()[0];
+    for pos in player.get_tail() {
+        data[index(1, pos)] = 1.0;
+    }
+    data[index(2, &player.get_pos())] = 1.0;
+    for (pos, fruit) in game.fruits() {
+        if fruit == player.fruit() {
+            data[index(3, pos)] = 1.0;
+        }
+    }
+    for (pos, terrain) in game.board().terrain_cells() {
+        if let Some(channel) = terrain_channel(terrain, &Directions::UP) {
+            data[index(5 + channel, &pos)] = 1.0;
+        }
+    }
+    Observation {
+        shape: vec![5 + TERRAIN_CHANNELS, max_y, max_x],
+        data,
+    }
+}
+
+/// Used to turn a position relative to the snake, where up is forward, into one on the board
+fn to_board(head: &Position, dirr: &Directions, x: isize, y: isize) -> Position {
+    let (x, y) = match dirr {
+        Directions::UP => (x, y),
+        Directions::RIGHT => (-y, x),
+        Directions::DOWN => (-x, -y),
+        Directions::LEFT => (y, -x),
+    };
+    Position::new(head.x + x, head.y + y)
+}
+
+/// Used to turn a step relative to the snake, where up is forward, into a direction on the board
+fn turned(dirr: &Directions, x: isize, y: isize) -> Directions {
+    let step = to_board(&Position::new(0, 0), dirr, x, y);
+    match (step.x, step.y) {
+        (0, -1) => Directions::UP,
+        (0, 1) => Directions::DOWN,
+        (-1, 0) => Directions::LEFT,
+        _ => Directions::RIGHT,
+    }
+}
+
+fn egocentric(game: &Game, radius: usize) -> Observation {
+    let side = 2 * radius + 1;
+    let size = side * side;
+    let mut data = vec![0.0; (3 + TERRAIN_CHANNELS) * size];
+    let player = &game.players()[0];
+    let (head, dirr) = (player.get_pos(), player.get_dirr());
+
+    for y in 0..side {
+        for x in 0..side {
+            let pos = wrap(game, to_board(&head, &dirr, x as isize - radius as isize, y as isize - radius as isize));
+            if (x, y) == (radius, radius) {
+                continue;
+            }
+            if is_obstacle(game, &pos) {
+                data[y * side + x] = 1.0;
+            } else if is_fruit(game, &pos) {
+                data[size + y * side + x] = 1.0;
+            } else if game.board().check_position(&pos, &Items::PORTAL) {
+                data[2 * size + y * side + x] = 1.0;
+            }
+            if let Some(channel) = terrain_channel(game.board().terrain(&pos), &dirr) {
+                data[(3 + channel) * size + y * side + x] = 1.0;
+            }
+        }
+    }
+    Observation {
+        shape: vec![3 + TERRAIN_CHANNELS, side, side],
+        data,
+    }
+}
+
+fn features(game: &Game) -> Observation {
+    let player = &game.players()[0];
+    let (head, dirr) = (player.get_pos(), player.get_dirr());
+    let danger = |x: isize, y: isize| {
+        let pos = game.board().next_pos(&head, &turned(&dirr, x, y));
+        is_obstacle(game, &pos) as u8 as f32
+    };
+    let fruit = game
+        .fruits()
+        .iter()
+        .filter(|(_, fruit)| fruit == player.fruit())
+        .min_by_key(|(pos, _)| pos.x.abs_diff(head.x) + pos.y.abs_diff(head.y))
+        .map(|(pos, _)| pos.clone())
+        .unwrap_or_else(|| head.clone());
+    let (max_x, max_y) = game.board().get_max_size();
+
+    let data = vec![
+        danger(0, -1),
+        danger(-1, 0),
+        danger(1, 0),
+        (dirr == Directions::UP) as u8 as f32,
+        (dirr == Directions::DOWN) as u8 as f32,
+        (dirr == Directions::LEFT) as u8 as f32,
+        (dirr == Directions::RIGHT) as u8 as f32,
+        (fruit.y < head.y) as u8 as f32,
+        (fruit.y > head.y) as u8 as f32,
+        (fruit.x < head.x) as u8 as f32,
+        (fruit.x > head.x) as u8 as f32,
+        player.get_size() as f32 / (max_x * max_y) as f32,
+    ];
+    Observation {
+        shape: vec![data.len()],
+        data,
+    }
+}
+
+#[cfg(test)]
+mod env_test {
+    use crate::env::*;
+
+    #[test]
+    fn test_shapes() {
+        let level = Level::builtin("classic").unwrap();
+        let mut env = SnakeEnv::new(level.clone(), Encoding::Grid, 100);
+        let obs = env.reset(0);
+        assert_eq!(vec![12, 16, 16], obs.shape);
+        assert_eq!(12 * 16 * 16, obs.data.len());
+
+        let mut env = SnakeEnv::new(level.clone(), Encoding::Egocentric(2), 100);
+        assert_eq!(vec![10, 5, 5], env.reset(0).shape);
+
+        let mut env = SnakeEnv::new(level, Encoding::Features, 100);
+        assert_eq!(vec![12], env.reset(0).shape);
+    }
+
+    #[test]
+    fn test_egocentric_wraps() {
+        // Facing left from the middle of the board, so the rows of the view are the columns of the board
+        let mut env = SnakeEnv::new(Level::builtin("gaps").unwrap(), Encoding::Egocentric(9), 100);
+        let obs = env.reset(0);
+        let side = 19;
+        // Through the gaps in the left and right walls
+        assert_eq!(0.0, obs.data[9]);
+        assert_eq!(0.0, obs.data[17 * side + 9]);
+        // Past the corner, which is a wall on the other side too
+        assert_eq!(1.0, obs.data[18]);
+    }
+
+    #[test]
+    fn test_portals() {
+        let level = Level::builtin("portals").unwrap();
+        let mut env = SnakeEnv::new(level.clone(), Encoding::Grid, 100);
+        let obs = env.reset(0);
+        let size = 24 * 16;
+        assert_eq!(4.0, obs.data[4 * size..].iter().sum::<f32>());
+        assert_eq!(1.0, obs.data[4 * size + 3 * 24 + 4]);
+
+        // Seven cells around the head at 12,8 reach the portals on the right, but not those on the left
+        let mut env = SnakeEnv::new(level, Encoding::Egocentric(7), 100);
+        let obs = env.reset(0);
+        let size = 15 * 15;
+        assert_eq!(2.0, obs.data[2 * size..].iter().sum::<f32>());
+    }
+
+    #[test]
+    fn test_terrain() {
+        let level = Level::builtin("terrain").unwrap();
+        let mut env = SnakeEnv::new(level.clone(), Encoding::Grid, 100);
+        let obs = env.reset(0);
+        let channel = |channel: usize, x: usize, y: usize| obs.data[(channel * 24 + y) * 32 + x];
+        assert_eq!(1.0, channel(6, 3, 4));
+        assert_eq!(1.0, channel(5, 4, 10));
+        assert_eq!(1.0, channel(7, 22, 10));
+        assert_eq!(1.0, channel(8, 12, 6));
+        assert_eq!(1.0, channel(9, 19, 6));
+
+        // Facing left, so the arrows pointing up point right of the snake, and those pointing down to its left
+        let mut env = SnakeEnv::new(level, Encoding::Egocentric(7), 100);
+        let obs = env.reset(0);
+        let size = 15 * 15;
+        let arrows = |channel: usize| obs.data[channel * size..(channel + 1) * size].iter().sum::<f32>();
+        assert_eq!(0.0, arrows(6) + arrows(7));
+        assert!(arrows(8) > 0.0);
+        assert!(arrows(9) > 0.0);
+    }
+
+    #[test]
+    fn test_full() {
+        // Two free cells, where the snake fills the board by eating the fruit on the other one
+        let level = Level::parse("full", "####\n#..#\n####\n").unwrap();
+        let mut env = SnakeEnv::new(level, Encoding::Features, 100);
+        env.reset(0);
+        let (_, reward, done) = env.step(Directions::LEFT);
+        assert!(done);
+        assert_eq!(FRUIT_REWARD + FULL_REWARD, reward);
+        let game = env.game().unwrap();
+        assert!(game.is_full());
+        assert!(game.players()[0].is_alive());
+    }
+
+    #[test]
+    fn test_death() {
+        let mut env = SnakeEnv::new(Level::builtin("classic").unwrap(), Encoding::Features, 100);
+        let obs = env.reset(0);
+        // Facing left, with nothing straight ahead
+        assert_eq!(0.0, obs.data[0]);
+        let mut done = false;
+        let mut reward = 0.0;
+        for _ in 0..16 {
+            let (_, step_reward, step_done) = env.step(Directions::LEFT);
+            reward = step_reward;
+            done = step_done;
+            if done {
+                break;
+            }
+        }
+        assert!(done);
+        assert_eq!(DEATH_REWARD, reward);
+    }
+}
@@ -0,0 +1,34 @@
+use std::{fmt::Display, io, path::PathBuf};
+
+/// Everything that can stop the game from starting or running
+#[derive(Debug)]
+pub enum Error {
+    /// The options can not make a game
+    Config(String),
+    /// The terminal could not be set up or drawn to
+    Terminal(io::Error),
+    /// Something could not be done to a file, with what it was
+    File {
+        path: PathBuf,
+        action: &'static str,
+        err: io::Error,
+    },
+    /// The state of the game could not be turned into JSON
+    State(serde_json::Error),
+    /// An external bot could not be started
+    Bot(String, io::Error),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Config(message) => write!(f, "{}", message),
+            Error::Terminal(err) => write!(f, "could not use the terminal: {}", err),
+            Error::File { path, action, err } => write!(f, "could not {} {}: {}", action, path.display(), err),
+            Error::State(err) => write!(f, "could not write the state of the game as JSON: {}", err),
+            Error::Bot(command, err) => write!(f, "could not start the bot {:?}: {}", command, err),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{
+    board::Board,
+    controller::helper_enums::Directions,
+    placement::FreeCells,
+    snake::Position,
+    team::MAX_TEAMS,
+    Items,
+};
+
+/// The number of ticks between every step of moving fruit, when no number is given
+pub const DEFAULT_MOVE_EVERY: usize = 4;
+
+/// How fruit moves once it has been placed
+#[derive(Clone, Debug, PartialEq)]
+pub enum Motion {
+    /// A step in any direction, or none
+    Wander,
+    /// A step away from the nearest head, if there is one
+    Flee,
+}
+
+/// What happens to fruit after it has been placed
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FruitRules {
+    /// The number of ticks a fruit stays before it is placed again elsewhere, if it goes away
+    lifetime: Option<usize>,
+    motion: Option<Motion>,
+    /// The number of ticks between every step of the fruit
+    move_every: usize,
+    /// The tick the fruit on every position goes away
+    expires: HashMap<Position, usize>,
+}
+
+impl FruitRules {
+    /// Used to parse a list of rules separated by commas, from expire:N, and wander:N or flee:N
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut rules = FruitRules {
+            move_every: DEFAULT_MOVE_EVERY,
+            ..Default::default()
+        };
+        for part in text.split(',') {
+            let (name, value) = part.split_once(':').unwrap_or((part, ""));
+            let value: usize = match (name, value) {
+                ("wander" | "flee", "") => DEFAULT_MOVE_EVERY,
+                _ => value
+                    .parse()
+                    .map_err(|_| format!("invalid number {:?} for {}", value, name))?,
+            };
+            if value == 0 {
+                return Err(format!("{} needs a number above 0", name));
+            }
+            let motion = match name {
+                "expire" => {
+                    rules.lifetime = Some(value);
+                    continue;
+                }
+                "wander" => Motion::Wander,
+                "flee" => Motion::Flee,
+                _ => return Err(format!("unknown fruit rule {:?}, expected expire, wander or flee", name)),
+            };
+            if rules.motion.is_some() {
+                return Err("fruit can either wander or flee, not both".to_string());
+            }
+            rules.motion = Some(motion);
+            rules.move_every = value;
+        }
+        Ok(rules)
+    }
+
+    /// Used to start the time of a fruit that was placed on the position
+    pub(crate) fn placed(&mut self, pos: &Position, tick: usize) {
+        if let Some(lifetime) = self.lifetime {
+            self.expires.insert(pos.clone(), tick + lifetime);
+        }
+    }
+
+    /// Used to stop the time of a fruit that was eaten
+    pub(crate) fn eaten(&mut self, pos: &Position) {
+        self.expires.remove(pos);
+    }
+
+    /// Used to get the number of ticks before the fruit on the position goes away, if it does
+    pub fn left(&self, pos: &Position, tick: usize) -> Option<usize> {
+        self.expires.get(pos).map(|expires| expires.saturating_sub(tick))
+    }
+
+    /// Moves the fruit forward to the tick, and takes away the fruit that has expired
+    /// Returns the kinds of fruit that were taken away, which have to be placed again
+    pub(crate) fn update(
+        &mut self,
+        tick: usize,
+        board: &mut Board,
+        free: &mut FreeCells,
+        fruits: &mut Vec<(Position, Items)>,
+        heads: &[Position],
+        rng: &mut impl Rng,
+    ) -> Vec<Items> {
+        let (expired, kept) = std::mem::take(fruits)
+            .into_iter()
+            .partition::<Vec<_>, _>(|(pos, _)| self.expires.get(pos).is_some_and(|expires| *expires <= tick));
+        *fruits = kept;
+        for (pos, _) in &expired {
+            self.expires.remove(pos);
+            board[pos] = Items::EMPTY;
+            free.insert(pos);
+        }
+
+        if let Some(motion) = &self.motion {
+            if tick.is_multiple_of(self.move_every) {
+                // Food left by dead snakes stays where they died
+                for (pos, kind) in fruits.iter_mut().filter(|(_, kind)| *kind != Items::FOOD) {
+                    let Some(next) = step(motion, pos, board, free, heads, rng) else {
+                        continue;
+                    };
+                    board[&*pos] = Items::EMPTY;
+                    board[&next] = kind.clone();
+                    free.insert(pos);
+                    free.remove(&next);
+                    if let Some(expires) = self.expires.remove(pos) {
+                        self.expires.insert(next.clone(), expires);
+                    }
+                    *pos = next;
+                }
+            }
+        }
+        expired.into_iter().map(|(_, kind)| kind).collect()
+    }
+}
+
+/// Used to parse how many fruits of every team are on the board at once,
+/// either one count for every team, or counts for the teams in order separated by commas
+pub fn parse_counts(text: &str) -> Result<Vec<usize>, String> {
+    let counts: Vec<usize> = text
+        .split(',')
+        .map(|count| count.trim().parse())
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("invalid fruit count {:?}, expected N or N,N", text))?;
+    if counts.len() > MAX_TEAMS {
+        return Err(format!("there are only {} kinds of fruit, one for every team", MAX_TEAMS));
+    }
+    if counts.contains(&0) {
+        return Err("every team needs at least one fruit".to_string());
+    }
+    Ok(counts)
+}
+
+/// Used to get the distance from a position to the nearest head
+fn nearest(pos: &Position, heads: &[Position]) -> usize {
+    heads
+        .iter()
+        .map(|head| pos.x.abs_diff(head.x) + pos.y.abs_diff(head.y))
+        .min()
+        .unwrap_or(usize::MAX)
+}
+
+/// Used to choose where a fruit steps to, if it moves at all
+fn step(
+    motion: &Motion,
+    pos: &Position,
+    board: &Board,
+    cells: &FreeCells,
+    heads: &[Position],
+    rng: &mut impl Rng,
+) -> Option<Position> {
+    let free: Vec<Position> = [Directions::UP, Directions::DOWN, Directions::LEFT, Directions::RIGHT]
+        .iter()
+        .map(|dirr| board.next_pos(pos, dirr))
+        .filter(|next| cells.contains(next))
+        .collect();
+    match motion {
+        Motion::Wander if rng.gen_bool(0.5) => free.choose(rng).cloned(),
+        Motion::Wander => None,
+        Motion::Flee => {
+            let best = free.iter().map(|next| nearest(next, heads)).max()?;
+            if best <= nearest(pos, heads) {
+                return None;
+            }
+            let furthest: Vec<&Position> = free.iter().filter(|next| nearest(next, heads) == best).collect();
+            furthest.choose(rng).map(|next| (*next).clone())
+        }
+    }
+}
+
+#[cfg(test)]
+mod fruit_test {
+    use rand::{prelude::StdRng, SeedableRng};
+
+    use crate::{fruit::*, occupancy::Occupancy};
+
+    #[test]
+    fn test_parse() {
+        let rules = FruitRules::parse("expire:30,flee:2").unwrap();
+        assert_eq!(Some(30), rules.lifetime);
+        assert_eq!(Some(Motion::Flee), rules.motion);
+        assert_eq!(2, rules.move_every);
+        assert_eq!(DEFAULT_MOVE_EVERY, FruitRules::parse("wander").unwrap().move_every);
+        assert!(FruitRules::parse("expire").is_err());
+        assert!(FruitRules::parse("expire:0").is_err());
+        assert!(FruitRules::parse("wander,flee").is_err());
+        assert!(FruitRules::parse("rot:3").is_err());
+
+        assert_eq!(Ok(vec![5]), parse_counts("5"));
+        assert_eq!(Ok(vec![3, 1]), parse_counts("3, 1"));
+        assert!(parse_counts("0").is_err());
+        assert!(parse_counts("1,2,3").is_err());
+        assert!(parse_counts("many").is_err());
+    }
+
+    #[test]
+    fn test_update() {
+        let mut board = Board::new(8, 8, false);
+        let occupancy = Occupancy::new(board.get_max_size(), &[]);
+        let mut free = FreeCells::new(&board, &occupancy);
+        let heads = [Position::new(2, 2)];
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut rules = FruitRules::parse("expire:3,flee:1").unwrap();
+        let mut fruits = vec![(Position::new(3, 3), Items::FRUIT)];
+        board[Position::new(3, 3)] = Items::FRUIT;
+        free.remove(&Position::new(3, 3));
+        rules.placed(&Position::new(3, 3), 0);
+
+        // Away from the head, keeping its time
+        assert!(rules.update(1, &mut board, &mut free, &mut fruits, &heads, &mut rng).is_empty());
+        let pos = fruits[0].0.clone();
+        assert!(pos == Position::new(4, 3) || pos == Position::new(3, 4));
+        assert!(board.check_position(&pos, &Items::FRUIT));
+        assert!(board.check_position(&Position::new(3, 3), &Items::EMPTY));
+        assert_eq!(Some(1), rules.left(&pos, 2));
+
+        let expired = rules.update(3, &mut board, &mut free, &mut fruits, &heads, &mut rng);
+        assert_eq!(vec![Items::FRUIT], expired);
+        assert!(fruits.is_empty());
+        assert!(board.check_position(&pos, &Items::EMPTY));
+        assert!(free.contains(&pos));
+
+        // Food does not move
+        let mut fruits = vec![(Position::new(3, 3), Items::FOOD)];
+        board[Position::new(3, 3)] = Items::FOOD;
+        free.remove(&Position::new(3, 3));
+        for tick in 4..20 {
+            rules.update(tick, &mut board, &mut free, &mut fruits, &heads, &mut rng);
+        }
+        assert_eq!(vec![(Position::new(3, 3), Items::FOOD)], fruits);
+    }
+}
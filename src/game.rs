@@ -0,0 +1,704 @@
+use std::time::{Duration, Instant};
+
+use rand::{
+    prelude::{thread_rng, StdRng},
+    Rng, SeedableRng,
+};
+
+use crate::{
+    board::Board,
+    fruit::FruitRules,
+    obstacles::Obstacles,
+    occupancy::Occupancy,
+    placement::{FreeCells, Placement},
+    snake::{Happen, Position, Snake},
+    state::GameState,
+    survival::Survival,
+    team::Team,
+    terrain::Terrain,
+    Items,
+};
+
+/// Contains everything about a running game, and the rules for moving it forward
+pub struct Game {
+    board: Board,
+    players: Vec<Snake>,
+    /// Where the snakes are, which is kept up to date as they move
+    occupancy: Occupancy,
+    /// The cells with nothing on them, which is kept up to date with the board and the occupancy
+    free: FreeCells,
+    /// Set when the board has one-way cells, so the cells the heads can reach change as they move
+    one_way: bool,
+    teams: Vec<Team>,
+    fruits: Vec<(Position, Items)>,
+    /// The fruits that found no free cell yet, which are placed as soon as there is one
+    pending: Vec<Items>,
+    placement: Placement,
+    obstacles: Option<Obstacles>,
+    fruit_rules: Option<FruitRules>,
+    /// The number of fruits of every team on the board at once
+    fruit_counts: Vec<usize>,
+    /// Set when the game goes on after a snake dies
+    survival: Option<Survival>,
+    /// Set when there is no free cell left for a fruit
+    full: bool,
+    ticks: usize,
+    started: Instant,
+    rng: StdRng,
+}
+
+impl Game {
+    /// Creates a new [`Game`], and places the first fruit of every team
+    pub fn new(board: Board, players: Vec<Snake>, teams: Vec<Team>) -> Self {
+        Self::seeded(board, players, teams, thread_rng().gen())
+    }
+
+    /// Creates a new [`Game`] where the fruit is placed the same way every time for the same seed
+    pub fn seeded(board: Board, mut players: Vec<Snake>, teams: Vec<Team>, seed: u64) -> Self {
+        for (index, player) in players.iter_mut().enumerate() {
+            player.set_player(index);
+        }
+        let occupancy = Occupancy::new(board.get_max_size(), &players);
+        let free = FreeCells::new(&board, &occupancy);
+        let one_way = board.terrain_cells().any(|(_, terrain)| matches!(terrain, Terrain::Arrow(_)));
+        // The walls moving along the paths of the level
+        let obstacles = (!board.paths().is_empty()).then(|| Obstacles::default().with_paths(board.paths()));
+        let mut game = Game {
+            board,
+            players,
+            occupancy,
+            free,
+            one_way,
+            teams,
+            fruits: vec![],
+            pending: vec![],
+            placement: Placement::default(),
+            obstacles,
+            fruit_rules: None,
+            fruit_counts: vec![1],
+            survival: None,
+            full: false,
+            ticks: 0,
+            started: Instant::now(),
+            rng: StdRng::seed_from_u64(seed),
+        };
+        game.place_first_fruits();
+        game
+    }
+
+    /// Used to change where fruit is placed, which also moves the first fruits
+    pub fn with_placement(mut self, placement: Placement) -> Self {
+        self.placement = placement;
+        self.replace_first_fruits();
+        self
+    }
+
+    /// Used to change how many fruits of every team are on the board at once,
+    /// where the last count is used for the teams after it
+    pub fn with_fruits(mut self, counts: Vec<usize>) -> Self {
+        self.fruit_counts = counts;
+        self.replace_first_fruits();
+        self
+    }
+
+    /// Used to add walls that appear and move while the game is played,
+    /// next to the walls moving along the paths of the level
+    pub fn with_obstacles(mut self, obstacles: Obstacles) -> Self {
+        self.obstacles = Some(obstacles.with_paths(self.board.paths()));
+        self
+    }
+
+    /// Used to make fruit go away after a while, or move around
+    pub fn with_fruit_rules(mut self, mut rules: FruitRules) -> Self {
+        for (pos, _) in &self.fruits {
+            rules.placed(pos, self.ticks);
+        }
+        self.fruit_rules = Some(rules);
+        self
+    }
+
+    /// Used to go on until one snake is left, where dead snakes turn into food,
+    /// and come back after a while or watch
+    pub fn with_survival(mut self, survival: Survival) -> Self {
+        self.survival = Some(survival);
+        self
+    }
+
+    /// Used to place the fruits of the teams by turns, so no team is left without when the board is crowded
+    fn place_first_fruits(&mut self) {
+        let counts: Vec<usize> = self
+            .teams
+            .iter()
+            .map(|team| {
+                let count = self.fruit_counts.get(team.get_id()).or(self.fruit_counts.last());
+                count.copied().unwrap_or(1)
+            })
+            .collect();
+        let most = counts.iter().max().copied().unwrap_or(0);
+        let fruits: Vec<Items> = (0..most)
+            .flat_map(|round| {
+                self.teams
+                    .iter()
+                    .zip(&counts)
+                    .filter(move |(_, count)| round < **count)
+                    .map(|(team, _)| team.fruit().clone())
+            })
+            .collect();
+        self.place_fruits(fruits);
+    }
+
+    fn replace_first_fruits(&mut self) {
+        for (pos, _) in std::mem::take(&mut self.fruits) {
+            self.board[&pos] = Items::EMPTY;
+            self.free.update(&pos, &self.board, &self.occupancy);
+        }
+        self.pending.clear();
+        self.full = false;
+        self.place_first_fruits();
+    }
+
+    /// Used to set new fruits on free cells of the board, chosen by the placement,
+    /// after the fruits that are still waiting for a free cell
+    /// The fruits that do not fit are kept, and tried again on the next tick
+    /// Returns false if there is no free cell at all, and no fruit left that a snake can eat, so the board is full
+    fn place_fruits(&mut self, fruits: Vec<Items>) -> bool {
+        self.pending.extend(fruits);
+        if self.pending.is_empty() {
+            return true;
+        }
+        let heads: Vec<Position> = self.players.iter().map(|player| player.get_pos()).collect();
+        let chosen = self
+            .placement
+            .choose_many(&mut self.free, &self.board, &heads, self.pending.len(), &mut self.rng);
+        if chosen.is_empty() {
+            let edible = |kind: &Items| {
+                *kind == Items::FOOD
+                    || self.players.iter().any(|player| player.is_alive() && player.fruit() == kind)
+            };
+            self.full = !self.fruits.iter().any(|(_, kind)| edible(kind));
+            return !self.full;
+        }
+        let placed: Vec<Items> = self.pending.drain(..chosen.len()).collect();
+        for (pos, fruit) in chosen.into_iter().zip(placed) {
+            if let Some(rules) = self.fruit_rules.as_mut() {
+                rules.placed(&pos, self.ticks);
+            }
+            self.board[&pos] = fruit.clone();
+            self.free.update(&pos, &self.board, &self.occupancy);
+            self.fruits.push((pos, fruit));
+        }
+        true
+    }
+
+    /// Moves every snake forward, one step unless the terrain under its head says otherwise
+    /// Returns false if a snake died, or the board is full, and the game is over, else true
+    pub fn tick(&mut self) -> bool {
+        if self.full || self.is_over() {
+            return false;
+        }
+        self.ticks += 1;
+
+        let starts: Vec<Position> = self.players.iter().map(|player| player.get_pos()).collect();
+        let mut crashes = vec![];
+        let mut eaten = vec![];
+        for (index, player) in self.players.iter_mut().enumerate() {
+            if !player.is_alive() {
+                continue;
+            }
+            for _ in 0..player.steps(&self.board) {
+                match player.move_snake(index, &self.board, &mut self.occupancy, &mut self.fruits) {
+                    Happen::Some(_) => {
+                        if let Some(rules) = self.fruit_rules.as_mut() {
+                            rules.eaten(&player.get_pos());
+                        }
+                        let fruit = std::mem::replace(&mut self.board[player.get_pos()], Items::EMPTY);
+                        self.free.update(&player.get_pos(), &self.board, &self.occupancy);
+                        // Food left by dead snakes is not replaced
+                        if fruit != Items::FOOD {
+                            eaten.push(fruit);
+                        }
+                    }
+                    Happen::Break => {
+                        player.kill();
+                        crashes.push(self.board.next_pos(&player.get_pos(), &player.get_dirr()));
+                        break;
+                    }
+                    Happen::None => self.free.update(&player.get_pos(), &self.board, &self.occupancy),
+                };
+            }
+        }
+
+        // The snakes move one after another, so a snake that ran into a head that got there first this tick
+        // takes that snake with it, as if they had moved at the same time
+        for pos in &crashes {
+            for index in self.occupancy.players(pos).collect::<Vec<_>>() {
+                if self.players[index].get_pos() == *pos && starts[index] != *pos {
+                    self.players[index].kill();
+                }
+            }
+        }
+        if !crashes.is_empty() && self.survival.is_none() {
+            return false;
+        }
+
+        for (index, player) in self.players.iter_mut().enumerate() {
+            while let Some(last_pos) = player.get_back() {
+                self.occupancy.remove(&last_pos, index);
+                self.free.update(&last_pos, &self.board, &self.occupancy);
+            }
+        }
+        if self.one_way {
+            self.free.walls_changed();
+        }
+        self.update_obstacles(&mut eaten);
+        self.update_fruit(&mut eaten);
+        let placed = self.place_fruits(eaten);
+        self.update_survival();
+        placed && !self.is_over()
+    }
+
+    /// Used to check if the game is over, which is when a snake has died,
+    /// or when one snake is left if the game goes on after deaths
+    pub fn is_over(&self) -> bool {
+        match &self.survival {
+            Some(survival) => survival.is_over(&self.players),
+            None => self.players.iter().any(|player| !player.is_alive()),
+        }
+    }
+
+    /// Used to turn the bodies of the snakes that died into food, and bring back the snakes whose time has come
+    fn update_survival(&mut self) {
+        if self.survival.is_none() {
+            return;
+        }
+        let dead: Vec<usize> = (0..self.players.len())
+            .filter(|index| !self.players[*index].is_alive() && !self.players[*index].get_tail().is_empty())
+            .collect();
+        for index in dead {
+            self.bury(index);
+            if let Some(survival) = self.survival.as_mut() {
+                survival.died(index, self.ticks);
+            }
+        }
+        if let Some(survival) = self.survival.as_mut() {
+            survival.update(
+                self.ticks,
+                &self.board,
+                &mut self.occupancy,
+                &mut self.free,
+                &mut self.players,
+                &mut self.rng,
+            );
+        }
+    }
+
+    /// Used to turn the body of the dead snake at index into food, on the cells nothing else is on
+    fn bury(&mut self, index: usize) {
+        for pos in self.players[index].take_tail() {
+            self.occupancy.remove(&pos, index);
+            if self.board.check_position(&pos, &Items::EMPTY) && self.occupancy.is_empty(&pos) {
+                self.board[&pos] = Items::FOOD;
+                self.fruits.push((pos.clone(), Items::FOOD));
+            }
+            self.free.update(&pos, &self.board, &self.occupancy);
+        }
+    }
+
+    /// Used to move the obstacles, where the snakes on a cell that became a wall die,
+    /// and the fruit on it is added to the fruits that have to be placed again, while food is gone for good
+    fn update_obstacles(&mut self, fruits: &mut Vec<Items>) {
+        let Some(obstacles) = self.obstacles.as_mut() else {
+            return;
+        };
+        let heads: Vec<Position> = self.players.iter().map(|player| player.get_pos()).collect();
+        let solid = obstacles.update(
+            self.ticks,
+            &mut self.board,
+            &self.occupancy,
+            &mut self.free,
+            &heads,
+            &mut self.rng,
+        );
+        for pos in solid {
+            for index in self.occupancy.players(&pos).collect::<Vec<_>>() {
+                self.players[index].kill();
+            }
+            if let Some(index) = self.fruits.iter().position(|(fruit_pos, _)| *fruit_pos == pos) {
+                let (_, fruit) = self.fruits.remove(index);
+                if fruit != Items::FOOD {
+                    fruits.push(fruit);
+                }
+            }
+        }
+    }
+
+    /// Used to move the fruit, where the fruit that has expired is added to the fruits that have to be placed again
+    fn update_fruit(&mut self, fruits: &mut Vec<Items>) {
+        let Some(rules) = self.fruit_rules.as_mut() else {
+            return;
+        };
+        let heads: Vec<Position> = self.players.iter().map(|player| player.get_pos()).collect();
+        fruits.extend(rules.update(
+            self.ticks,
+            &mut self.board,
+            &mut self.free,
+            &mut self.fruits,
+            &heads,
+            &mut self.rng,
+        ));
+    }
+
+    /// Takes a snake out of the game, which ends it unless the game goes on after deaths
+    pub fn disqualify(&mut self, index: usize) {
+        self.players[index].kill();
+        // Buried right away, so it does not come back
+        if self.survival.is_some() {
+            self.bury(index);
+        }
+    }
+
+    /// Used to get the number of ticks before the dead snake at index comes back, if it does
+    pub fn respawn_in(&self, index: usize) -> Option<usize> {
+        self.survival.as_ref().and_then(|survival| survival.respawn_in(index, self.ticks))
+    }
+
+    /// Used to get the number of times the snake at index can still come back, if dead snakes do
+    pub fn lives_left(&self, index: usize) -> Option<usize> {
+        self.survival.as_ref().and_then(|survival| survival.lives_left(index))
+    }
+
+    /// Used to get the score of a team, which is the fruits eaten by all its snakes
+    pub fn team_score(&self, team: &Team) -> usize {
+        self.players
+            .iter()
+            .filter(|player| player.get_team() == team.get_id())
+            .map(|player| player.eaten())
+            .sum()
+    }
+
+    /// Takes a snapshot of the game, which can be serialized
+    pub fn state(&self) -> GameState {
+        GameState::new(self)
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn players(&self) -> &[Snake] {
+        &self.players
+    }
+
+    pub fn players_mut(&mut self) -> &mut [Snake] {
+        &mut self.players
+    }
+
+    /// Used to get the cells that are about to become walls
+    pub fn warnings(&self) -> Vec<Position> {
+        self.obstacles.as_ref().map(Obstacles::warnings).unwrap_or_default()
+    }
+
+    pub(crate) fn occupancy(&self) -> &Occupancy {
+        &self.occupancy
+    }
+
+    /// Used to get every cell of the board, with the snakes on it
+    pub fn cells(&self) -> Vec<Vec<Items>> {
+        let mut cells = self.board.get_vec().clone();
+        for player in &self.players {
+            for pos in player.get_tail() {
+                cells[pos.y as usize][pos.x as usize] = player.get_items();
+            }
+        }
+        cells
+    }
+
+    pub fn teams(&self) -> &[Team] {
+        &self.teams
+    }
+
+    pub fn fruits(&self) -> &[(Position, Items)] {
+        &self.fruits
+    }
+
+    /// Used to get the number of ticks before the fruit on the position goes away, if it does
+    pub fn countdown(&self, pos: &Position) -> Option<usize> {
+        self.fruit_rules.as_ref().and_then(|rules| rules.left(pos, self.ticks))
+    }
+
+    /// Checks if there was no free cell left for a fruit, which ends the game
+    pub fn is_full(&self) -> bool {
+        self.full
+    }
+
+    pub fn ticks(&self) -> usize {
+        self.ticks
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+}
+
+#[cfg(test)]
+mod game_test {
+    use crate::{
+        controller::helper_enums::Directions, game::*, level::Level, obstacles::MOVE_EVERY, survival::Survival,
+        terrain::Terrain,
+    };
+
+    fn get_game() -> Game {
+        let team = Team::new(0, true).unwrap();
+        let snake = Snake::new(Position::new(6, 4), &team);
+        Game::new(Board::new(8, 8, false), vec![snake], vec![team])
+    }
+
+    #[test]
+    fn test_tick() {
+        let mut game = get_game();
+        assert_eq!(1, game.fruits().len());
+        assert!(game.tick());
+        assert_eq!(1, game.ticks());
+        assert_eq!(Position::new(5, 4), game.players()[0].get_pos());
+    }
+
+    #[test]
+    fn test_head_on() {
+        // Both heads reach the same cell on the second tick, and neither wins by moving first
+        for first in 0..2 {
+            let team = Team::new(0, true).unwrap();
+            let mut players = vec![Snake::new(Position::new(8, 8), &team), Snake::new(Position::new(6, 6), &team)];
+            players[1].turn(Directions::DOWN);
+            players.rotate_left(first);
+            let mut game = Game::seeded(Board::new(16, 16, false), players, vec![team], 0);
+            assert!(game.tick());
+            assert!(!game.tick());
+            assert!(game.players().iter().all(|player| !player.is_alive()));
+        }
+    }
+
+    #[test]
+    fn test_occupancy() {
+        let team = Team::new(0, true).unwrap();
+        let snake = Snake::new(Position::new(10, 4), &team);
+        let mut game = Game::seeded(Board::new(16, 16, true), vec![snake], vec![team], 0);
+        for _ in 0..8 {
+            game.players_mut()[0].eat();
+            assert!(game.tick());
+            let (max_x, max_y) = game.board().get_max_size();
+            assert_eq!(&Occupancy::new((max_x, max_y), game.players()), game.occupancy());
+            for row in game.board().get_vec() {
+                assert!(!row.contains(&Items::SNAKE));
+            }
+            let fresh = FreeCells::new(game.board(), game.occupancy());
+            assert_eq!(fresh.len(), game.free.len());
+            for y in 0..max_y {
+                for x in 0..max_x {
+                    let pos = Position::new(x as isize, y as isize);
+                    assert_eq!(fresh.contains(&pos), game.free.contains(&pos));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_players() {
+        // Teammates still look different
+        let team = Team::new(0, true).unwrap();
+        let players = vec![Snake::new(Position::new(3, 2), &team), Snake::new(Position::new(3, 5), &team)];
+        let game = Game::seeded(Board::new(8, 8, false), players, vec![team], 0);
+        assert_eq!(vec![0, 1], game.players().iter().map(Snake::get_player).collect::<Vec<_>>());
+        assert_eq!(Items::SNAKE, game.cells()[2][3]);
+        assert_eq!(Items::OSNAKE, game.cells()[5][3]);
+    }
+
+    #[test]
+    fn test_full() {
+        // One free cell, which the snake fills by eating the fruit on it
+        let team = Team::new(0, true).unwrap();
+        let snake = Snake::new(Position::new(2, 1), &team);
+        let mut game = Game::seeded(Board::new(4, 3, false), vec![snake], vec![team], 0);
+        assert_eq!(vec![(Position::new(1, 1), Items::FRUIT)], game.fruits());
+        assert!(!game.tick());
+        assert!(game.is_full());
+        assert!(game.players()[0].is_alive());
+        assert_eq!(1, game.players()[0].eaten());
+    }
+
+    #[test]
+    fn test_full_fruits() {
+        // Three free cells for three fruits, which are all eaten before the board is full
+        let team = Team::new(0, true).unwrap();
+        let snake = Snake::new(Position::new(4, 1), &team);
+        let mut game = Game::seeded(Board::new(6, 3, false), vec![snake], vec![team], 0).with_fruits(vec![3]);
+        assert_eq!(3, game.fruits().len());
+        assert!(game.tick());
+        assert!(!game.is_full());
+        assert_eq!(2, game.fruits().len());
+        assert!(game.tick());
+        assert!(!game.tick());
+        assert!(game.is_full());
+        assert!(game.fruits().is_empty());
+        assert!(game.players()[0].is_alive());
+        assert_eq!(3, game.players()[0].eaten());
+    }
+
+    #[test]
+    fn test_fruits() {
+        let teams = vec![Team::new(0, true).unwrap(), Team::new(1, true).unwrap()];
+        let players = vec![
+            Snake::new(Position::new(8, 5), &teams[0]),
+            Snake::new(Position::new(8, 10), &teams[1]),
+        ];
+        let mut game = Game::seeded(Board::new(16, 16, false), players, teams, 0).with_fruits(vec![5, 2]);
+        let count = |game: &Game, kind: Items| game.fruits().iter().filter(|(_, fruit)| *fruit == kind).count();
+        assert_eq!((5, 2), (count(&game, Items::FRUIT), count(&game, Items::OFRUIT)));
+        for _ in 0..6 {
+            game.tick();
+            assert_eq!((5, 2), (count(&game, Items::FRUIT), count(&game, Items::OFRUIT)));
+            for (pos, fruit) in game.fruits() {
+                assert!(game.board().check_position(pos, fruit));
+            }
+        }
+    }
+
+    #[test]
+    fn test_pending() {
+        // Four free cells for six fruits, shared by the teams
+        let teams = vec![Team::new(0, true).unwrap(), Team::new(1, true).unwrap()];
+        let players = vec![
+            Snake::new(Position::new(1, 1), &teams[0]),
+            Snake::new(Position::new(6, 1), &teams[1]),
+        ];
+        let mut game = Game::seeded(Board::new(8, 3, false), players, teams, 0).with_fruits(vec![3]);
+        let count = |game: &Game, kind: Items| game.fruits().iter().filter(|(_, fruit)| *fruit == kind).count();
+        assert_eq!((2, 2), (count(&game, Items::FRUIT), count(&game, Items::OFRUIT)));
+        assert_eq!(vec![Items::FRUIT, Items::OFRUIT], game.pending);
+        assert!(!game.is_full());
+
+        // Placed as soon as a cell is free
+        let (pos, _) = game.fruits.pop().unwrap();
+        game.board[&pos] = Items::EMPTY;
+        game.free.update(&pos, &game.board, &game.occupancy);
+        assert!(game.place_fruits(vec![]));
+        assert_eq!(vec![Items::OFRUIT], game.pending);
+        assert_eq!(Some(&(pos, Items::FRUIT)), game.fruits().last());
+    }
+
+    #[test]
+    fn test_survival() {
+        let game = |rule: &str| {
+            let team = Team::new(0, true).unwrap();
+            let players = vec![Snake::new(Position::new(3, 5), &team), Snake::new(Position::new(12, 10), &team)];
+            let survival = Survival::parse(rule).unwrap();
+            Game::seeded(Board::new(16, 16, false), players, vec![team], 0).with_survival(survival)
+        };
+        // The first snake runs into the wall on the third tick
+        let mut spectate = game("spectate");
+        assert!(spectate.tick() && spectate.tick());
+        assert!(!spectate.tick());
+        assert!(spectate.is_over());
+        assert_eq!(None, spectate.respawn_in(0));
+
+        let mut respawn = game("respawn:2");
+        for _ in 0..3 {
+            assert!(respawn.tick());
+        }
+        assert!(!respawn.players()[0].is_alive());
+        assert_eq!(Some(2), respawn.respawn_in(0));
+        let food: Vec<&Position> = respawn
+            .fruits()
+            .iter()
+            .filter(|(_, fruit)| *fruit == Items::FOOD)
+            .map(|(pos, _)| pos)
+            .collect();
+        assert!(!food.is_empty());
+        assert!(food.iter().all(|pos| respawn.occupancy().is_empty(pos)));
+        assert!(respawn.tick() && respawn.tick());
+        assert!(respawn.players()[0].is_alive());
+        assert_eq!(1, respawn.players()[0].get_tail().len());
+    }
+
+    #[test]
+    fn test_obstacles() {
+        let team = Team::new(0, true).unwrap();
+        let snake = Snake::new(Position::new(6, 4), &team);
+        let obstacles = Obstacles::parse("shrink:1,warning:2").unwrap();
+        let mut game = Game::seeded(Board::new(9, 9, false), vec![snake], vec![team], 0).with_obstacles(obstacles);
+        assert!(game.tick());
+        assert!(game.warnings().contains(&Position::new(1, 1)));
+        // Food under the closing ring is not placed again
+        game.board[Position::new(1, 1)] = Items::FOOD;
+        game.fruits.push((Position::new(1, 1), Items::FOOD));
+        game.free.update(&Position::new(1, 1), &game.board, &game.occupancy);
+        assert!(game.tick());
+        // The snake is inside the first ring, which is now closed
+        assert!(game.tick());
+        assert!(game.board().check_position(&Position::new(1, 1), &Items::WALL));
+        assert!(game.players()[0].is_alive());
+        assert_eq!(Position::new(3, 4), game.players()[0].get_pos());
+        assert!(game.fruits().iter().all(|(pos, _)| !game.board().check_position(pos, &Items::WALL)));
+        assert_eq!(vec![Items::FRUIT], game.fruits().iter().map(|(_, fruit)| fruit.clone()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_paths() {
+        let text = "################\n#..............#\n#..............#\n#..............#\n################\n\npath 1,1 3,1\n";
+        let level = Level::parse("file", text).unwrap();
+        let team = Team::new(0, true).unwrap();
+        let snake = Snake::new(level.spawn_position(0, 1), &team);
+        let mut game = Game::seeded(level.board(), vec![snake], vec![team], 0);
+        assert!(game.board().check_position(&Position::new(1, 1), &Items::WALL));
+        assert_eq!(vec![Position::new(2, 1)], game.warnings());
+        assert!(game.fruits().iter().all(|(pos, _)| *pos != Position::new(2, 1)));
+        for _ in 0..MOVE_EVERY {
+            assert!(game.tick());
+        }
+        assert!(game.board().check_position(&Position::new(1, 1), &Items::EMPTY));
+        assert!(game.board().check_position(&Position::new(2, 1), &Items::WALL));
+    }
+
+    #[test]
+    fn test_terrain() {
+        let team = Team::new(0, true).unwrap();
+        let snake = Snake::new(Position::new(12, 8), &team);
+        let terrain = vec![
+            (Position::new(11, 8), Terrain::Mud),
+            (Position::new(10, 8), Terrain::Boost),
+            (Position::new(9, 8), Terrain::Ice),
+            (Position::new(8, 8), Terrain::Ice),
+            (Position::new(4, 8), Terrain::Arrow(Directions::RIGHT)),
+        ];
+        let board = Board::new(16, 16, false).with_terrain(terrain);
+        let mut game = Game::seeded(board, vec![snake], vec![team], 0);
+        let tick = |game: &mut Game| {
+            assert!(game.tick());
+            game.players()[0].get_pos()
+        };
+        assert_eq!(Position::new(11, 8), tick(&mut game));
+        // Stuck in the mud every other tick
+        assert_eq!(Position::new(11, 8), tick(&mut game));
+        assert_eq!(Position::new(10, 8), tick(&mut game));
+        // Two steps on the boost lane, onto the ice
+        assert_eq!(Position::new(8, 8), tick(&mut game));
+        for _ in 0..3 {
+            game.players_mut()[0].turn(Directions::UP);
+            assert_eq!(Directions::LEFT, game.players()[0].get_dirr());
+            tick(&mut game);
+        }
+        assert_eq!(Position::new(5, 8), game.players()[0].get_pos());
+        // Against the arrow
+        assert!(!game.tick());
+        assert!(!game.players()[0].is_alive());
+    }
+
+    #[test]
+    fn test_state_json() {
+        let mut game = get_game();
+        game.tick();
+        let json = game.state().to_json().unwrap();
+        assert!(json.contains("\"tick\":1"));
+        assert!(json.contains("\"direction\":\"LEFT\""));
+        assert!(json.contains("\"tail\":[{\"x\":5,\"y\":4},{\"x\":6,\"y\":4}]"));
+    }
+}
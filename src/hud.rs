@@ -0,0 +1,126 @@
+use snake::{board, level, scores::Record, Game, Level, Snake, Team};
+use tui::style::{Modifier, Style};
+
+use crate::theme::{Segment, Theme};
+
+use std::time::Duration;
+
+/// A titled part of the stats panel
+#[derive(Clone, Debug, PartialEq)]
+pub struct Section {
+    pub title: String,
+    pub style: Style,
+    pub lines: Vec<String>,
+}
+
+impl Section {
+    fn new(title: &str, style: Style, lines: Vec<String>) -> Self {
+        Section {
+            title: title.to_string(),
+            style: style.add_modifier(Modifier::BOLD),
+            lines,
+        }
+    }
+}
+
+/// Used to write a duration as minutes and seconds, like 01:05
+pub fn clock(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
+
+/// Used to get how many times the snakes have moved every second
+fn speed(game: &Game) -> f64 {
+    let secs = game.elapsed().as_secs_f64();
+    if secs > 0.0 {
+        game.ticks() as f64 / secs
+    } else {
+        0.0
+    }
+}
+
+/// Used to build the stats panel
+///
+/// best is the record to beat, if the level has been played before
+/// controls are the keys of every player, or None for bots
+pub fn hud(game: &Game, theme: &Theme, best: Option<&Record>, controls: &[Option<&str>]) -> Vec<Section> {
+    let mut sections: Vec<Section> = game
+        .players()
+        .iter()
+        .enumerate()
+        .map(|(index, player)| {
+            let (_, style) = theme.snake(player.get_team(), player.get_player(), &Segment::Head);
+            let mut lines = vec![
+                format!("Length: {}", player.get_size()),
+                format!("Score:  {}", player.eaten()),
+            ];
+            if let Some(lives) = game.lives_left(index) {
+                lines.push(format!("Lives:  {}", lives));
+            }
+            if !player.is_alive() {
+                lines.push(match game.respawn_in(index) {
+                    Some(ticks) => format!("Back in {}", ticks),
+                    None => "Out".to_string(),
+                });
+            }
+            Section::new(&format!("Player {}", index + 1), style, lines)
+        })
+        .collect();
+    if game.players().len() > game.teams().len() {
+        let lines = game
+            .teams()
+            .iter()
+            .map(|team| format!("Team {}: {}", team.get_id() + 1, game.team_score(team)))
+            .collect();
+        sections.push(Section::new("Teams", Style::default(), lines));
+    }
+
+    let score = best.map_or_else(|| "-".to_string(), |best| best.score.to_string());
+    let mut lines = vec![
+        format!("Time:    {}", clock(game.elapsed())),
+        format!("Speed:   {:.1}/s", speed(game)),
+        format!("To beat: {}", score),
+    ];
+    if let Some(fastest) = best.and_then(|best| best.fastest()) {
+        lines.push(format!("Fastest: {}", clock(fastest)));
+    }
+    sections.push(Section::new("Game", Style::default(), lines));
+
+    let mut lines: Vec<String> = controls
+        .iter()
+        .enumerate()
+        .filter_map(|(index, keys)| keys.map(|keys| format!("P{}: {}", index + 1, keys)))
+        .collect();
+    lines.push("Quit: q".to_string());
+    sections.push(Section::new("Controls", Style::default(), lines));
+    sections
+}
+
+/// Used to build the stats panel of a game that has not started yet, on the smallest board,
+/// to know how much room the panel takes before the size of the board is chosen
+///
+/// Every line that can be shown is there, with the numbers as short as they get
+pub fn preview(teams: &[Team], players: usize, theme: &Theme, controls: &[Option<&str>]) -> Vec<Section> {
+    let level = Level::new("preview", level::MIN_WIDTH, board::MIN_SIZE, false);
+    let snakes = (0..players)
+        .map(|index| Snake::new(level.spawn_position(index, players), &teams[index % teams.len()]))
+        .collect();
+    let game = Game::new(level.board(), snakes, teams.to_vec());
+    let best = Record {
+        score: 0,
+        fastest_ms: Some(0),
+    };
+    hud(&game, theme, Some(&best), controls)
+}
+
+#[cfg(test)]
+mod hud_test {
+    use crate::hud::*;
+
+    #[test]
+    fn test_clock() {
+        assert_eq!("00:00", clock(Duration::from_secs(0)));
+        assert_eq!("01:15", clock(Duration::from_secs(75)));
+        assert_eq!("61:01", clock(Duration::from_millis(3_661_999)));
+    }
+}
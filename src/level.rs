@@ -0,0 +1,345 @@
+use std::{collections::BTreeMap, fs, ops::RangeInclusive, path::Path};
+
+use crate::{
+    board::{self, Board},
+    controller::helper_enums::Directions,
+    snake::Position,
+    terrain::Terrain,
+    Items,
+};
+
+/// The layout of a board, and where the snakes start on it
+#[derive(Clone, Debug)]
+pub struct Level {
+    name: String,
+    size_x: usize,
+    size_y: usize,
+    gaps: bool,
+    /// The pairs of cells that lead to each other
+    portals: Vec<(Position, Position)>,
+    /// The cells with terrain other than plain ground
+    terrain: Vec<(Position, Terrain)>,
+    /// Every cell of the board, for levels drawn in a file instead of made from the size and gaps
+    layout: Option<Vec<Vec<Items>>>,
+    /// The corners of the paths walls move along
+    paths: Vec<Vec<Position>>,
+}
+
+/// The number of free cells a snake needs in front of it when it spawns, heading left
+pub const SPAWN_ROOM: usize = 2;
+
+/// The narrowest board, which leaves [`SPAWN_ROOM`] free cells in front of snakes spawning in the middle
+pub const MIN_WIDTH: usize = 2 * (SPAWN_ROOM + 1);
+
+/// The names of the levels that are always available
+pub const BUILTIN: [&str; 6] = ["classic", "gaps", "arena", "arena-gaps", "portals", "terrain"];
+
+/// Used to get every cell of a rectangle, with the same terrain
+fn area(xs: RangeInclusive<isize>, ys: RangeInclusive<isize>, terrain: Terrain) -> Vec<(Position, Terrain)> {
+    ys.flat_map(|y| xs.clone().map(move |x| Position::new(x, y)))
+        .map(|pos| (pos, terrain.clone()))
+        .collect()
+}
+
+/// Used to parse a position written as X,Y
+fn parse_position(text: &str) -> Result<Position, String> {
+    let invalid = || format!("invalid position {:?}, expected X,Y", text);
+    let (x, y) = text.split_once(',').ok_or_else(invalid)?;
+    Ok(Position::new(x.parse().map_err(|_| invalid())?, y.parse().map_err(|_| invalid())?))
+}
+
+impl Level {
+    /// Creates a new [`Level`]
+    pub fn new(name: &str, size_x: usize, size_y: usize, gaps: bool) -> Self {
+        Level {
+            name: name.to_string(),
+            size_x,
+            size_y,
+            gaps,
+            portals: vec![],
+            terrain: vec![],
+            layout: None,
+            paths: vec![],
+        }
+    }
+
+    /// Used to read a level drawn as text, one line per row and one character per cell
+    ///
+    /// `#` is a wall and `.` an empty cell, and a letter is one end of a portal,
+    /// where the same letter has to be used for both ends
+    /// `~` is mud, `+` a boost lane, `*` ice, and `^`, `v`, `<` and `>` are arrows, so `v` is not a portal
+    ///
+    /// After the board and a blank line, every line like `path 3,2 3,8 10,8` adds a wall
+    /// that moves back and forth along the path through the corners
+    pub fn parse(name: &str, text: &str) -> Result<Self, String> {
+        let rows: Vec<&str> = text.lines().map(str::trim_end).take_while(|line| !line.is_empty()).collect();
+        let mut paths = vec![];
+        for line in text.lines().skip(rows.len()).filter(|line| !line.trim().is_empty()) {
+            let Some(corners) = line.trim().strip_prefix("path ") else {
+                return Err(format!("unexpected line {:?} after the board, expected path X,Y X,Y ...", line));
+            };
+            paths.push(corners.split_whitespace().map(parse_position).collect::<Result<Vec<_>, _>>()?);
+        }
+        let size_x = rows.first().map_or(0, |row| row.chars().count());
+        let mut layout = vec![vec![Items::EMPTY; size_x]; rows.len()];
+        let mut ends: BTreeMap<char, Vec<Position>> = BTreeMap::new();
+        let mut terrain = vec![];
+        for (y, row) in rows.iter().enumerate() {
+            if row.chars().count() != size_x {
+                return Err(format!("row {} is {} wide, but the first row is {}", y + 1, row.chars().count(), size_x));
+            }
+            for (x, cell) in row.chars().enumerate() {
+                let pos = Position::new(x as isize, y as isize);
+                match cell {
+                    '#' => layout[y][x] = Items::WALL,
+                    '.' => (),
+                    '~' => terrain.push((pos, Terrain::Mud)),
+                    '+' => terrain.push((pos, Terrain::Boost)),
+                    '*' => terrain.push((pos, Terrain::Ice)),
+                    '^' => terrain.push((pos, Terrain::Arrow(Directions::UP))),
+                    'v' => terrain.push((pos, Terrain::Arrow(Directions::DOWN))),
+                    '<' => terrain.push((pos, Terrain::Arrow(Directions::LEFT))),
+                    '>' => terrain.push((pos, Terrain::Arrow(Directions::RIGHT))),
+                    'a'..='z' | 'A'..='Z' => ends.entry(cell).or_default().push(pos),
+                    _ => return Err(format!("unknown cell {:?} at {},{}", cell, x, y)),
+                }
+            }
+        }
+        let mut portals = vec![];
+        for (letter, ends) in ends {
+            let [a, b] = <[Position; 2]>::try_from(ends)
+                .map_err(|ends| format!("portal {:?} has {} ends, but it needs 2", letter, ends.len()))?;
+            portals.push((a, b));
+        }
+        let mut level = Self::new(name, size_x, rows.len(), false)
+            .with_portals(portals)
+            .with_terrain(terrain)
+            .with_paths(paths);
+        level.layout = Some(layout);
+        Ok(level)
+    }
+
+    /// Used to get a built-in level by name, or load a level from a file
+    pub fn load(name: &str) -> Result<Self, String> {
+        if let Some(level) = Self::builtin(name) {
+            return Ok(level);
+        }
+        let text = fs::read_to_string(name).map_err(|err| {
+            format!(
+                "{:?} is not one of {}, and could not be read as a file: {}",
+                name,
+                BUILTIN.join(", "),
+                err
+            )
+        })?;
+        let stem = Path::new(name).file_stem().and_then(|stem| stem.to_str()).unwrap_or(name);
+        Self::parse(stem, &text).map_err(|err| format!("invalid level file {:?}: {}", name, err))
+    }
+
+    /// Used to add portals between pairs of cells
+    pub fn with_portals(mut self, portals: Vec<(Position, Position)>) -> Self {
+        self.portals = portals;
+        self
+    }
+
+    /// Used to lay terrain on cells
+    pub fn with_terrain(mut self, terrain: Vec<(Position, Terrain)>) -> Self {
+        self.terrain = terrain;
+        self
+    }
+
+    /// Used to add walls that move back and forth along paths, given by their corners
+    pub fn with_paths(mut self, paths: Vec<Vec<Position>>) -> Self {
+        self.paths = paths;
+        self
+    }
+
+    /// Used to get one of the built-in levels by name
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "classic" => Some(Self::new(name, 16, 16, false)),
+            "gaps" => Some(Self::new(name, 16, 16, true)),
+            "arena" => Some(Self::new(name, 32, 24, false)),
+            "arena-gaps" => Some(Self::new(name, 32, 24, true)),
+            "portals" => Some(Self::new(name, 24, 16, false).with_portals(vec![
+                (Position::new(4, 3), Position::new(19, 12)),
+                (Position::new(19, 3), Position::new(4, 12)),
+            ])),
+            "terrain" => Some(Self::new(name, 32, 24, false).with_terrain(
+                [
+                    area(3..=28, 4..=4, Terrain::Boost),
+                    area(3..=28, 19..=19, Terrain::Boost),
+                    area(4..=9, 10..=14, Terrain::Mud),
+                    area(22..=27, 10..=14, Terrain::Ice),
+                    area(12..=12, 6..=17, Terrain::Arrow(Directions::UP)),
+                    area(19..=19, 6..=17, Terrain::Arrow(Directions::DOWN)),
+                ]
+                .concat(),
+            )),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Used to check that the level can be played by the number of players
+    /// Returns why it can not, if it can not
+    pub fn validate(&self, players: usize) -> Result<(), String> {
+        let (size_x, size_y) = (self.size_x, self.size_y);
+        if !(MIN_WIDTH..=board::MAX_SIZE).contains(&size_x) || !(board::MIN_SIZE..=board::MAX_SIZE).contains(&size_y) {
+            return Err(format!(
+                "the board is {}x{}, but it has to be from {}x{} to {}x{}",
+                size_x,
+                size_y,
+                MIN_WIDTH,
+                board::MIN_SIZE,
+                board::MAX_SIZE,
+                board::MAX_SIZE
+            ));
+        }
+        if self.gaps && (size_x < board::MIN_GAPS_SIZE || size_y < board::MIN_GAPS_SIZE) {
+            return Err(format!(
+                "the board is {}x{}, but it has to be at least {}x{} to have gaps in the walls",
+                size_x,
+                size_y,
+                board::MIN_GAPS_SIZE,
+                board::MIN_GAPS_SIZE
+            ));
+        }
+        let spawns: Vec<Position> = (0..players).map(|index| self.spawn_position(index, players)).collect();
+        let inside = |pos: &Position| pos.y > 0 && pos.y < size_y as isize - 1;
+        if !spawns.iter().all(inside) || spawns.windows(2).any(|pair| pair[0] == pair[1]) {
+            return Err(format!("the board is {} high, which is too low for {} snakes", size_y, players));
+        }
+        let outside = |pos: &Position| pos.x < 0 || pos.y < 0 || pos.x >= size_x as isize || pos.y >= size_y as isize;
+        let ends: Vec<&Position> = self.portals.iter().flat_map(|(a, b)| [a, b]).collect();
+        for (index, end) in ends.iter().enumerate() {
+            if outside(end) {
+                return Err(format!("the portal at {},{} is outside the board", end.x, end.y));
+            }
+            if ends[..index].contains(end) || spawns.contains(end) {
+                return Err(format!("the portal at {},{} is on top of something else", end.x, end.y));
+            }
+        }
+        if let Some((pos, _)) = self.terrain.iter().find(|(pos, _)| outside(pos)) {
+            return Err(format!("the terrain at {},{} is outside the board", pos.x, pos.y));
+        }
+        for path in &self.paths {
+            let start = path.first().ok_or("a path needs at least one corner")?;
+            if let Some(pos) = path.iter().find(|pos| outside(pos)) {
+                return Err(format!("the path through {},{} goes outside the board", pos.x, pos.y));
+            }
+            if let Some(pair) = path.windows(2).find(|pair| pair[0].x != pair[1].x && pair[0].y != pair[1].y) {
+                return Err(format!(
+                    "the path from {},{} to {},{} is not a straight line",
+                    pair[0].x, pair[0].y, pair[1].x, pair[1].y
+                ));
+            }
+            if ends.contains(&start) || spawns.contains(start) {
+                return Err(format!("the path starting at {},{} is on top of something else", start.x, start.y));
+            }
+        }
+        if let Some(layout) = &self.layout {
+            // The snakes start heading left
+            let blocked = spawns
+                .iter()
+                .flat_map(|spawn| (0..=SPAWN_ROOM as isize).map(move |step| Position::new(spawn.x - step, spawn.y)))
+                .find(|pos| layout[pos.y as usize][pos.x as usize] != Items::EMPTY);
+            if let Some(pos) = blocked {
+                return Err(format!("the snakes need room to start, but {},{} is a wall", pos.x, pos.y));
+            }
+        }
+        Ok(())
+    }
+
+    /// Used to tell levels apart when keeping high scores, since custom levels share a name
+    pub fn key(&self) -> String {
+        let gaps = if self.gaps { "-gaps" } else { "" };
+        format!("{}-{}x{}{}", self.name, self.size_x, self.size_y, gaps)
+    }
+
+    /// Creates a new board with the layout of the level
+    pub fn board(&self) -> Board {
+        let board = match &self.layout {
+            Some(layout) => Board::from_vec(layout.clone()),
+            None => Board::new(self.size_x, self.size_y, self.gaps),
+        };
+        board
+            .with_portals(self.portals.clone())
+            .with_terrain(self.terrain.clone())
+            .with_paths(self.paths.clone())
+    }
+
+    /// Used to get the starting position of a snake, so that the snakes are spread out vertically
+    pub fn spawn_position(&self, index: usize, players: usize) -> Position {
+        Position::new(
+            (self.size_x / 2) as isize,
+            ((index + 1) * self.size_y / (players + 1)) as isize,
+        )
+    }
+}
+
+#[cfg(test)]
+mod level_test {
+    use crate::level::*;
+
+    #[test]
+    fn test_validate() {
+        for name in BUILTIN {
+            assert_eq!(Ok(()), Level::builtin(name).unwrap().validate(2));
+        }
+        assert_eq!(Ok(()), Level::new("custom", 6, 5, false).validate(2));
+        assert_eq!(Ok(()), Level::new("custom", 100, 7, true).validate(1));
+        assert!(Level::new("custom", 3, 16, false).validate(1).is_err());
+        assert!(Level::new("custom", 16, 1001, false).validate(1).is_err());
+        assert!(Level::new("custom", 6, 16, true).validate(1).is_err());
+        // Only one cell in front of the snake before the wall
+        assert!(Level::new("custom", 5, 16, false).validate(1).is_err());
+        assert!(Level::new("custom", 16, 5, false).validate(4).is_err());
+        let portal = |a: (isize, isize), b: (isize, isize)| vec![(Position::new(a.0, a.1), Position::new(b.0, b.1))];
+        let level = |portals| Level::new("custom", 16, 16, false).with_portals(portals);
+        assert_eq!(Ok(()), level(portal((0, 3), (15, 12))).validate(1));
+        assert!(level(portal((3, 3), (3, 16))).validate(1).is_err());
+        assert!(level(portal((3, 3), (3, 3))).validate(1).is_err());
+        // On top of where the snake starts
+        assert!(level(portal((3, 3), (8, 8))).validate(1).is_err());
+        let level = Level::new("custom", 16, 16, false).with_terrain(area(10..=16, 2..=3, Terrain::Mud));
+        assert!(level.validate(1).is_err());
+    }
+
+    #[test]
+    fn test_parse() {
+        let text = "#########\n#a......#\n#.......#\n#.#...a.#\n#########\n";
+        let level = Level::parse("file", text).unwrap();
+        assert_eq!(Ok(()), level.validate(1));
+        assert_eq!("file-9x5", level.key());
+        let board = level.board();
+        assert!(board.check_position(&Position::new(2, 3), &Items::WALL));
+        assert!(board.check_position(&Position::new(1, 1), &Items::PORTAL));
+        assert_eq!(Some(&Position::new(6, 3)), board.portal(&Position::new(1, 1)));
+
+        let level = Level::parse("file", "#######\n#~+*..#\n#^v<>.#\n#######\n").unwrap();
+        let board = level.board();
+        assert_eq!(&Terrain::Boost, board.terrain(&Position::new(2, 1)));
+        assert_eq!(&Terrain::Arrow(Directions::DOWN), board.terrain(&Position::new(2, 2)));
+        assert_eq!(7, board.terrain_cells().count());
+
+        let board = "#########\n#.......#\n#.......#\n#.......#\n#########\n\n";
+        let level = Level::parse("file", &format!("{}path 1,1 1,3 6,3\n", board)).unwrap();
+        assert_eq!(Ok(()), level.validate(1));
+        assert!(level.board().check_position(&Position::new(1, 1), &Items::WALL));
+        assert!(Level::parse("file", &format!("{}path 1,1 3,3\n", board)).unwrap().validate(1).is_err());
+        assert!(Level::parse("file", &format!("{}path 1,1 1,9\n", board)).unwrap().validate(1).is_err());
+        assert!(Level::parse("file", &format!("{}path 1;1\n", board)).is_err());
+
+        assert!(Level::parse("file", "#####\n#...#\n####\n").is_err());
+        assert!(Level::parse("file", "#####\n#.?.#\n#####\n").is_err());
+        assert!(Level::parse("file", "#####\n#.a.#\n#####\n").is_err());
+        assert!(Level::parse("file", "#####\n#...#\n#####\n\nmore\n").is_err());
+        // A wall right in front of where the snake starts
+        let level = Level::parse("file", "#########\n#.......#\n###.....#\n#.......#\n#########\n").unwrap();
+        assert!(level.validate(1).is_err());
+    }
+}
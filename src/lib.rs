@@ -0,0 +1,47 @@
+pub mod board;
+pub mod bot;
+pub mod controller;
+pub mod env;
+pub mod fruit;
+mod game;
+pub mod level;
+mod obstacles;
+mod occupancy;
+mod placement;
+pub mod scores;
+mod snake;
+pub mod state;
+mod survival;
+pub mod team;
+mod terrain;
+pub mod tournament;
+
+use serde::Serialize;
+
+pub use board::Board;
+pub use game::Game;
+pub use level::Level;
+pub use obstacles::Obstacles;
+pub use placement::{Placement, Zone};
+pub use snake::{Position, Snake};
+pub use survival::Survival;
+pub use team::Team;
+pub use terrain::Terrain;
+
+/// The most players that can be told apart, each with its own kind of snake
+pub const MAX_PLAYERS: usize = 2;
+
+/// Used to differentiate the different items
+#[derive(PartialEq, PartialOrd, Clone, Debug, Serialize)]
+pub enum Items {
+    WALL = 10,
+    EMPTY = 0,
+    SNAKE = 1,
+    OSNAKE = 2,
+    FRUIT = 3,
+    OFRUIT = 4,
+    /// One end of a portal, which leads to the other end
+    PORTAL = 5,
+    /// Left behind by a dead snake, which any snake can eat
+    FOOD = 6,
+}
@@ -1,292 +1,428 @@
-mod board;
-mod snake;
-mod terminal;
-mod controller;
 mod consts;
-
-use board::Board;
-use clap::Parser;
-use rand::{
-    prelude::{thread_rng, ThreadRng},
-    Rng,
+mod error;
+mod hud;
+mod render;
+mod terminal;
+mod theme;
+
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+use snake::{
+    board,
+    bot::{Bot, ExternalBot},
+    controller::{self, helper_enums::MoveOpt, Keys},
+    fruit::{self, FruitRules},
+    level::{self, Level},
+    scores::{HighScores, Record},
+    team::Team,
+    tournament::{BotSpec, Tournament},
+    Game, Obstacles, Placement, Snake, Survival,
 };
-
-use consts::*;
-use snake::{Position, Snake};
+use error::Error;
 use terminal::Term;
+use theme::{Palette, Theme};
 
 use crossterm::event::{poll, read, Event, KeyCode, KeyEvent, KeyModifiers};
-use tui::{text::{Span, Spans}, style::{Style, Color}};
 
 use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
     thread::sleep,
-    time::{Duration, Instant}, collections::VecDeque,
+    time::Duration,
 };
 
-///!Used to differentiate the different items
-#[derive(PartialEq, PartialOrd, Clone)]
-pub enum Items {
-    WALL = 10,
-    EMPTY = 0,
-    SNAKE = 1,
-    OSNAKE = 2,
-    FRUIT = 3,
-    OFRUIT = 4,
-}
+/// The number of fruits of every team, named so clap parses it as one value instead of many
+type FruitCounts = Vec<usize>;
 
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
 struct Args {
 
     /// Set the size in the x-direction
-    #[clap(short, default_value_t = 16)]
+    #[clap(short, default_value_t = board::DEFAULT)]
     x: usize,
 
     /// Set the size in the y-direction
-    #[clap(short, default_value_t = 16)]
+    #[clap(short, default_value_t = board::DEFAULT)]
     y: usize,
 
+    /// Make the board as large as the terminal allows, instead of using -x and -y
+    #[clap(short, long)]
+    auto_size: bool,
+
     /// Multiplayer
     #[clap(short, long)]
     multipl: bool,
 
-    /// Should there be gaps in the walls to go to the other side?
+    /// Should there be gaps in the walls to go to the other side? Needs a board of at least 7x7
     #[clap(short, long)]
     gaps: bool,
 
-    /// Should the snakes share the fruit
+    /// A built-in level or a level file to play on, instead of the size and gaps given
+    #[clap(long, value_name = "LEVEL", parse(try_from_str = Level::load))]
+    level: Option<Level>,
+
+    /// Should the snakes share the fruit, playing on the same team
     #[clap(short, long)]
     share_fruit: bool,
-}
 
-/// Used to set a new fruit on the board
-///
-/// Checks if a position is empty first
-pub fn fruit(board: &mut Board, fruit: &Items, fruits: &mut Vec<(Position, Items)>) {
-    let (max_x, max_y) = board.get_max_size();
-    let mut fruit_pos = get_rand_block(max_x, max_y);
-    while !board.check_position(&fruit_pos, &Items::EMPTY) {
-        fruit_pos = get_rand_block(max_x, max_y);
-    }
-    board[&fruit_pos] = (fruit.clone(), Span::from(""));
-    fruits.push((fruit_pos, fruit.clone()));
-}
+    /// Should the bodies of teammates not be lethal
+    #[clap(long)]
+    no_friendly_fire: bool,
+
+    /// Where new fruit is placed, either uniform, away, away:N or zone:X,Y,W,H with more zones after a ;
+    #[clap(long, value_name = "POLICY", default_value = "uniform", parse(try_from_str = Placement::parse))]
+    placement: Placement,
+
+    /// The number of fruits on the board at once, for every team, or for every team in order like 3,1
+    #[clap(long, value_name = "N", default_value = "1", parse(try_from_str = fruit::parse_counts))]
+    fruits: FruitCounts,
+
+    /// What fruit does once placed, from expire:N, and wander:N or flee:N, separated by commas
+    #[clap(long, value_name = "RULES", parse(try_from_str = FruitRules::parse))]
+    fruit_rules: Option<FruitRules>,
+
+    /// Walls that appear and move during play, from spawn:N, moving:N, shrink:N and warning:N separated by commas
+    #[clap(long, value_name = "OBSTACLES", parse(try_from_str = Obstacles::parse))]
+    obstacles: Option<Obstacles>,
+
+    /// Go on until one snake is left, where dead snakes turn into food, and either spectate or respawn:N,
+    /// with lives:N after a comma
+    #[clap(long, value_name = "RULE", parse(try_from_str = Survival::parse))]
+    last_standing: Option<Survival>,
+
+    /// Write the state of the game as JSON to this file, one line per tick
+    #[clap(long, value_name = "FILE")]
+    dump_state: Option<PathBuf>,
+
+    /// Let an external program control player one
+    #[clap(long, value_name = "COMMAND")]
+    p1_bot: Option<String>,
+
+    /// Let an external program control player two, which turns on multiplayer
+    #[clap(long, value_name = "COMMAND")]
+    p2_bot: Option<String>,
+
+    /// How long an external program has to answer each move, in milliseconds
+    #[clap(long, value_name = "MS", default_value_t = 100)]
+    bot_timeout: u64,
+
+    /// How the game looks, either classic, unicode, emoji, high-contrast, monochrome or a theme file
+    #[clap(long, value_name = "THEME", default_value = "classic", parse(try_from_str = Theme::load))]
+    theme: Theme,
 
-/// Used to get a random position on the board, inside the walls
-/// Returns that random position as a Position-type
-fn get_rand_block(max_x: usize, max_y: usize) -> Position {
-    let mut RNG: ThreadRng = thread_rng();
-    let x = RNG.gen_range(1..(max_x - 2)) as isize;
-    let y = RNG.gen_range(1..(max_y - 2)) as isize;
-    Position::new(x, y)
+    /// The colours used, either default, deuteranopia, protanopia, tritanopia or none
+    #[clap(long, value_name = "PALETTE", default_value = "default", parse(try_from_str = Palette::parse))]
+    palette: Palette,
+
+    /// Where the high scores are kept, instead of .snake-scores.json in the home directory
+    #[clap(long, value_name = "FILE")]
+    high_scores: Option<PathBuf>,
+
+    #[clap(subcommand)]
+    command: Option<Commands>,
 }
 
-fn gameloop_single(mut board: Board, mut player: Snake) {
-    let (max_x, max_y) = board.get_max_size();
-    let mut term = Term::new((max_x, max_y));
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Play headless matches between bots, and print the standings
+    Tournament(TournamentArgs),
+}
 
-    let mut fruits = vec![];
-    fruit(&mut board, player.fruit(), &mut fruits);
+#[derive(clap::Args, Debug)]
+struct TournamentArgs {
+    /// A bot taking part, either greedy, random or cmd: followed by a command
+    #[clap(long = "bot", value_name = "BOT", required = true, parse(try_from_str = BotSpec::parse))]
+    bots: Vec<BotSpec>,
 
-    let survival_time = Instant::now();
-    loop {
-        let curr_pos = player.get_pos();
-        board.change_position(&curr_pos, player.get_items());
-
-        if poll(Duration::from_millis(100)).unwrap() {
-            let event = read().unwrap();
-            player.mover(event);
-            match event {
-                Event::Key(KeyEvent {
-                    code: KeyCode::Char('q'),
-                    modifiers: KeyModifiers::NONE,
-                }) => break,
-                _ => (),
-            }
-        }
+    /// A level to play on, either classic, gaps, arena, arena-gaps, portals, terrain or a level file
+    #[clap(long = "level", value_name = "LEVEL", default_value = "classic", parse(try_from_str = Level::load))]
+    levels: Vec<Level>,
 
-        match player.move_snake(&mut board, &mut fruits) {
-            snake::Happen::Some(_) => fruit(&mut board, player.fruit(), &mut fruits),
-            snake::Happen::Break => break,
-            _ => {}
-        };
+    /// The number of seeds every pair of bots plays with
+    #[clap(long, default_value_t = 5)]
+    seeds: u64,
 
-        if let Some(last_pos) = player.get_back() {
-            board.remove_position(&last_pos);
-        }
+    /// The number of matches played at the same time
+    #[clap(short, long, default_value_t = 4)]
+    jobs: usize,
 
-        //going to top left corner
-        let secs = survival_time.elapsed().as_secs();
-        let mins = secs / 60;
-        let mut p_info = player.get_info();
-        p_info.push(format!("Time elapsed: {}:{}", mins, secs));
-        term.render(
-            add_fruits_n_pl(
-                board.get_vec(),
-                &vec![(player.get_tail().clone(), player.get_span())],
-                &fruits),
-            &p_info,
-        );
+    /// The number of ticks before a match ends, and the longest snake wins
+    #[clap(long, default_value_t = 1000)]
+    max_ticks: usize,
 
-        sleep(Duration::from_millis(20));
-    }
+    /// How long an external program has to answer each move, in milliseconds
+    #[clap(long, value_name = "MS", default_value_t = 100)]
+    bot_timeout: u64,
 }
 
 ///Main game loop
 ///
-///param max_size is the size of max x and y
-fn gameloop(mut board: Board, mut player_one: Snake, mut player_two: Snake, share: bool) {
-    let (max_x, max_y) = board.get_max_size();
-    let mut term = Term::new((max_x, max_y));
-
-    let survival_time = Instant::now();
-
-    let mut fruits = vec![];
-    if !share {
-        fruit(&mut board, player_one.fruit(), &mut fruits);
-        fruit(&mut board, player_two.fruit(), &mut fruits);
-    } else {
-        fruit(&mut board, player_two.fruit(), &mut fruits);
-    }
+///term is the terminal the game is drawn in
+///theme decides how the game looks
+///keys are the keys controlling the players, if any, and how they are shown
+///bots are the bots controlling the players, if any
+///dump is the file, and where it is, that the state of the game is written to every tick, if any
+///best is the record to beat, if there is one
+///Returns the reasons players were disqualified, if any were
+fn gameloop(
+    game: &mut Game,
+    term: &mut Term,
+    theme: &Theme,
+    keys: Vec<Option<(Keys, &str)>>,
+    mut bots: Vec<Option<Box<dyn Bot>>>,
+    mut dump: Option<(&Path, BufWriter<File>)>,
+    best: Option<&Record>,
+) -> Result<Vec<String>, Error> {
+    let mut reasons = vec![];
+    let controls: Vec<Option<&str>> = keys.iter().map(|keys| keys.map(|(_, shown)| shown)).collect();
+
     loop {
-        let curr_pos_1 = player_one.get_pos();
-        board.change_position(&curr_pos_1, player_one.get_items());
-
-        let curr_pos_2 = player_two.get_pos();
-        board.change_position(&curr_pos_2, player_two.get_items());
-
-        if poll(Duration::from_millis(100)).unwrap() {
-            let event = read().unwrap();
-            player_one.mover(event);
-            player_two.mover(event);
-            match event {
-                Event::Key(KeyEvent {
-                    code: KeyCode::Char('q'),
-                    modifiers: KeyModifiers::NONE,
-                }) => break,
-                _ => (),
+        if poll(Duration::from_millis(100)).map_err(Error::Terminal)? {
+            let event = read().map_err(Error::Terminal)?;
+            if let Event::Resize(_, _) = event {
+                term.resize().map_err(Error::Terminal)?;
+            }
+            for (player, keys) in game.players_mut().iter_mut().zip(&keys) {
+                if let Some(MoveOpt::Some(dirr)) = keys.map(|(keys, _)| keys(event)) {
+                    player.turn(dirr);
+                }
+            }
+            if let Event::Key(KeyEvent {
+                code: KeyCode::Char('q'),
+                modifiers: KeyModifiers::NONE,
+            }) = event
+            {
+                break;
             }
         }
 
-        match player_one.move_snake(&mut board, &mut fruits) {
-            snake::Happen::Some(_) => fruit(&mut board, player_one.fruit(), &mut fruits),
-            snake::Happen::Break => break,
-            _ => (),
-        };
-
-        match player_two.move_snake(&mut board, &mut fruits) {
-            snake::Happen::Some(_) => fruit(&mut board, player_two.fruit(), &mut fruits),
-            snake::Happen::Break => break,
-            _ => (),
-        };
+        for (index, slot) in bots.iter_mut().enumerate() {
+            if let Some(bot) = slot.as_mut().filter(|_| game.players()[index].is_alive()) {
+                match bot.next_move(game, index) {
+                    Ok(dirr) => game.players_mut()[index].turn(dirr),
+                    Err(err) => {
+                        // The game goes on without the bot, if it goes on after deaths
+                        game.disqualify(index);
+                        *slot = None;
+                        reasons.push(format!("Player {} was disqualified: {}", index + 1, err));
+                        if game.is_over() {
+                            return Ok(reasons);
+                        }
+                    }
+                }
+            }
+        }
 
-        if let Some(last_pos) = player_one.get_back() {
-            board.remove_position(&last_pos);
+        let alive = game.tick();
+        if let Some((path, dump)) = dump.as_mut() {
+            let json = game.state().to_json().map_err(Error::State)?;
+            writeln!(dump, "{}", json).map_err(|err| Error::File {
+                path: path.to_path_buf(),
+                action: "write the state to",
+                err,
+            })?;
         }
-        if let Some(last_pos) = player_two.get_back() {
-            board.remove_position(&last_pos);
+        if !alive {
+            break;
         }
 
-        //going to top left corner
-        let secs = survival_time.elapsed().as_secs();
-        let mins = secs / 60;
-        let mut p_info = player_one.get_info();
-        p_info.append(&mut player_two.get_info());
-        p_info.push(format!("Time elapsed: {}:{}", mins, secs));
-        term.render(
-            add_fruits_n_pl(
-                board.get_vec(),
-                &vec![(player_one.get_tail().clone(), player_one.get_span()), (player_two.get_tail().clone(), player_two.get_span())],
-                &fruits),
-                &p_info
-        );
+        term.render(game, theme, &hud::hud(game, theme, best, &controls)).map_err(Error::Terminal)?;
 
         sleep(Duration::from_millis(20));
     }
+    Ok(reasons)
 }
 
-fn add_fruits_n_pl<'a>(board: &'a Vec<Vec<(Items, Span)>>, players: &'a Vec<(VecDeque<Position>, Span)>, fruits: &Vec<(Position, Items)>) -> Vec<Spans<'a>>{
-    let mut rows: Vec<Vec<Span<'a>>> = vec![];
-    for each in board {
-        let row = each.iter().map(|x| x.1.clone()).collect();
-        rows.push(row);
-    }
-    for (pos_vec, span) in players {
-        for pos in pos_vec {
-            rows[pos.y as usize][pos.x as usize] = span.clone();
+/// Used to show a message until a key is pressed
+fn show_until_key(term: &mut Term, title: &str, lines: &[String]) -> Result<(), Error> {
+    loop {
+        term.message(title, lines).map_err(Error::Terminal)?;
+        match read().map_err(Error::Terminal)? {
+            Event::Key(_) => return Ok(()),
+            Event::Resize(_, _) => term.resize().map_err(Error::Terminal)?,
+            _ => (),
         }
     }
-    for (fruit_pos, fruit_type) in fruits {
-        let fruit = match fruit_type {
-            Items::FRUIT => Span::styled(FRUIT, Style::default().fg(Color::Red)),
-            Items::OFRUIT => Span::styled(FRUIT, Style::default().fg(Color::Blue)),
-            _ => Span::from(EMPTY),
-        };
-        rows[fruit_pos.y as usize][fruit_pos.x as usize] = fruit;
-    }
-    let mut para = vec![];
-    for each in rows {
-        para.push(Spans::from(each));
-    }
-    para
 }
 
 //Main-method
 //Takes arguments
 fn main() {
-    let args = Args::parse();
+    let args = parse_args();
+    if let Err(err) = run(args) {
+        eprintln!("error: {}", err);
+        let code = if let Error::Config(_) = err { 2 } else { 1 };
+        std::process::exit(code);
+    }
+}
+
+/// Used to parse the arguments, with the sizes of the board in the help taken from the limits the level checks
+fn parse_args() -> Args {
+    let x_help = format!("Set the size in the x-direction, from {} to {}", level::MIN_WIDTH, board::MAX_SIZE);
+    let y_help = format!("Set the size in the y-direction, from {} to {}", board::MIN_SIZE, board::MAX_SIZE);
+    let matches = Args::command()
+        .mut_arg("x", |arg| arg.help(x_help.as_str()))
+        .mut_arg("y", |arg| arg.help(y_help.as_str()))
+        .get_matches();
+    Args::from_arg_matches(&matches).unwrap_or_else(|err| err.exit())
+}
+
+/// Used to play the game, or the tournament, the arguments ask for
+fn run(args: Args) -> Result<(), Error> {
+    if let Some(Commands::Tournament(tournament)) = args.command {
+        if tournament.bots.len() < 2 {
+            return Err(Error::Config("a tournament needs at least two bots".to_string()));
+        }
+        for level in &tournament.levels {
+            level
+                .validate(2)
+                .map_err(|err| Error::Config(format!("the level {} is unplayable: {}", level.name(), err)))?;
+        }
+        let levels: Vec<&str> = tournament.levels.iter().map(|level| level.name()).collect();
+        println!("Levels: {}, seeds: {}", levels.join(", "), tournament.seeds);
+        let standings = Tournament::new(
+            tournament.bots,
+            tournament.levels,
+            (0..tournament.seeds).collect(),
+            tournament.jobs,
+            tournament.max_ticks,
+            Duration::from_millis(tournament.bot_timeout),
+        )
+        .run();
+        print!("{}", standings);
+        return Ok(());
+    }
+
+    let multipl = args.multipl || args.p2_bot.is_some();
+    let friendly_fire = !args.no_friendly_fire;
+    let mut teams = Team::all(friendly_fire);
+    if !multipl || args.share_fruit {
+        teams.truncate(1);
+    }
+
+    let mut slots = vec![((controller::get_player_one as Keys, controller::PLAYER_ONE_KEYS), args.p1_bot)];
+    if multipl {
+        slots.push(((controller::get_player_two, controller::PLAYER_TWO_KEYS), args.p2_bot));
+    }
+
+    let level = match args.level {
+        Some(level) => level,
+        None if args.auto_size => {
+            let controls: Vec<Option<&str>> =
+                slots.iter().map(|((_, shown), bot)| bot.is_none().then_some(*shown)).collect();
+            let stats = hud::preview(&teams, slots.len(), &args.theme, &controls);
+            let (size_x, size_y) = terminal::auto_size(&stats).map_err(Error::Terminal)?;
+            Level::new("custom", size_x, size_y, args.gaps)
+        }
+        None => Level::new("custom", args.x, args.y, args.gaps),
+    };
+    level.validate(multipl as usize + 1).map_err(Error::Config)?;
+    let timeout = Duration::from_millis(args.bot_timeout);
+    let mut players = vec![];
+    let mut keys = vec![];
+    let mut bots: Vec<Option<Box<dyn Bot>>> = vec![];
+    for (index, (player_keys, bot)) in slots.into_iter().enumerate() {
+        let team = &teams[index % teams.len()];
+        players.push(Snake::new(level.spawn_position(index, multipl as usize + 1), team));
+        match bot {
+            Some(command) => {
+                keys.push(None);
+                let bot = ExternalBot::spawn(&command, timeout).map_err(|err| Error::Bot(command.clone(), err))?;
+                bots.push(Some(Box::new(bot)));
+            }
+            None => {
+                keys.push(Some(player_keys));
+                bots.push(None);
+            }
+        }
+    }
 
-    let (size_x, size_y) = if args.x != 0 && args.y != 0 {
-        (args.x, args.y)
-    } else {
-        (board::DEFAULT, board::DEFAULT)
+    let dump = match &args.dump_state {
+        Some(path) => {
+            let file = File::create(path).map_err(|err| Error::File {
+                path: path.clone(),
+                action: "create the state dump",
+                err,
+            })?;
+            Some((path.as_path(), BufWriter::new(file)))
+        }
+        None => None,
     };
 
-    if args.multipl {
-        if args.share_fruit {
-            gameloop(
-                Board::new(size_x, size_y, args.gaps),
-                Snake::new(
-                    snake::Position::new((size_x / 2) as isize, (size_y / 2) as isize),
-                    Items::SNAKE,
-                    Items::FRUIT,
-                    controller::get_player_one,
-                ),
-                Snake::new(
-                    snake::Position::new((size_x / 2) as isize, (size_y / 2) as isize),
-                    Items::OSNAKE,
-                    Items::FRUIT,
-                    controller::get_player_two,
-                ),
-                args.share_fruit
-            );
-        } else {
-            gameloop(
-                Board::new(size_x, size_y, args.gaps),
-                Snake::new(
-                    snake::Position::new((size_x / 2) as isize, (size_y / 2) as isize),
-                    Items::SNAKE,
-                    Items::FRUIT,
-                    controller::get_player_one,
-                ),
-                Snake::new(
-                    snake::Position::new((size_x / 2) as isize, (size_y / 2) as isize),
-                    Items::OSNAKE,
-                    Items::OFRUIT,
-                    controller::get_player_two,
-                ),
-                args.share_fruit
-            );
+    let scores_path = args.high_scores.or_else(HighScores::default_path);
+    let mut scores = match scores_path.as_deref().map(HighScores::load) {
+        Some(Ok(scores)) => scores,
+        Some(Err(err)) => {
+            eprintln!("Could not read the high scores: {}", err);
+            HighScores::default()
         }
-    } else {
-        gameloop_single(
-            Board::new(size_x, size_y, args.gaps),
-            Snake::new(
-                snake::Position::new((size_x / 2) as isize, (size_y / 2) as isize),
-                Items::SNAKE,
-                Items::FRUIT,
-                controller::get_player_one,
-            ),
-        );
+        None => HighScores::default(),
     };
+    let best = scores.get(&level.key()).cloned();
+
+    let mut game = Game::new(level.board(), players, teams)
+        .with_placement(args.placement)
+        .with_fruits(args.fruits);
+    if let Some(obstacles) = args.obstacles {
+        game = game.with_obstacles(obstacles);
+    }
+    if let Some(rules) = args.fruit_rules {
+        game = game.with_fruit_rules(rules);
+    }
+    let last_standing = args.last_standing.is_some();
+    if let Some(survival) = args.last_standing {
+        game = game.with_survival(survival);
+    }
+    let theme = args.theme.with_palette(&args.palette.or_no_color());
+    let mut term = Term::new(game.board().get_max_size()).map_err(Error::Terminal)?;
+    let reasons = gameloop(&mut game, &mut term, &theme, keys, bots, dump, best.as_ref())?;
+    let time = game.elapsed();
+
+    let score = game.players().iter().map(|player| player.eaten()).max().unwrap_or(0);
+    let new_score = scores.record(&level.key(), score);
+    let mut changed = new_score;
+    if game.is_full() {
+        // Only a snake filling the board alone wins, and gets a time
+        let mut lines = vec![];
+        for (index, player) in game.players().iter().enumerate() {
+            lines.push(format!("Player {}: length {}", index + 1, player.get_size()));
+        }
+        lines.push(format!("Time: {}", hud::clock(time)));
+        let title = if game.players().len() == 1 {
+            if scores.complete(&level.key(), time) {
+                changed = true;
+                lines.push("A new fastest time!".to_string());
+            } else if let Some(fastest) = best.as_ref().and_then(|best| best.fastest()) {
+                lines.push(format!("Fastest: {}", hud::clock(fastest)));
+            }
+            "You win!"
+        } else {
+            "The board is full!"
+        };
+        lines.push(String::new());
+        lines.push("Press any key".to_string());
+        show_until_key(&mut term, title, &lines)?;
+    }
+    drop(term);
+
+    for reason in reasons {
+        eprintln!("{}", reason);
+    }
+    if last_standing && game.is_over() {
+        if let Some(index) = game.players().iter().position(|player| player.is_alive()) {
+            println!("Player {} is the last snake standing", index + 1);
+        }
+    }
+    if new_score {
+        println!("New high score: {}", score);
+    }
+    if let (true, Some(path)) = (changed, scores_path) {
+        scores.save(&path).map_err(|err| Error::File {
+            path,
+            action: "save the high scores to",
+            err,
+        })?;
+    }
+    Ok(())
 }
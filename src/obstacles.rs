@@ -0,0 +1,397 @@
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{
+    board::{self, Board},
+    controller::helper_enums::Directions,
+    occupancy::Occupancy,
+    placement::FreeCells,
+    snake::{opposite, Position},
+    Items,
+};
+
+/// The number of ticks a warning is shown, when no number is given
+pub const DEFAULT_WARNING: usize = 10;
+/// The number of ticks between every step of a moving obstacle
+pub const MOVE_EVERY: usize = 4;
+/// The closest to a head a new obstacle is placed
+const SPAWN_DISTANCE: usize = 3;
+
+/// A cell that becomes a wall
+#[derive(Clone, Debug, PartialEq)]
+struct Pending {
+    pos: Position,
+    /// The tick the cell becomes a wall
+    solid_at: usize,
+    /// The direction the wall moves in, if it is a moving obstacle
+    moving: Option<Directions>,
+}
+
+/// The way a moving obstacle goes
+#[derive(Clone, Debug, PartialEq)]
+enum Route {
+    /// In a straight line, turning around at walls
+    Bounce(Directions),
+    /// Along one of the paths, turning around at its ends
+    Path { path: usize, at: usize, back: bool },
+}
+
+/// An obstacle moving back and forth
+#[derive(Clone, Debug, PartialEq)]
+struct Mover {
+    pos: Position,
+    route: Route,
+}
+
+impl Mover {
+    /// Used to get the cell the obstacle goes to next, and how it goes on from there
+    /// Turning around at walls is left to the caller
+    fn ahead(&self, paths: &[Vec<Position>]) -> Option<(Position, Route)> {
+        match &self.route {
+            Route::Bounce(dirr) => Some((self.pos.step(dirr), self.route.clone())),
+            Route::Path { path, at, back } => {
+                let cells = &paths[*path];
+                let forward = if *back {
+                    at.checked_sub(1)
+                } else {
+                    Some(at + 1).filter(|at| *at < cells.len())
+                };
+                let (at, back) = match forward {
+                    Some(at) => (at, *back),
+                    None if *back => (at + 1, false),
+                    None => (at.checked_sub(1)?, true),
+                };
+                let route = Route::Path { path: *path, at, back };
+                cells.get(at).map(|pos| (pos.clone(), route))
+            }
+        }
+    }
+}
+
+/// Used to check if a position is on one of the rings of the arena that have been closed, or outside them
+fn is_closed(pos: &Position, rings: usize, board: &Board) -> bool {
+    if rings == 0 {
+        return false;
+    }
+    let (max_x, max_y) = board.get_max_size();
+    let (rings, max_x, max_y) = (rings as isize, max_x as isize, max_y as isize);
+    pos.x <= rings || pos.y <= rings || pos.x >= max_x - 1 - rings || pos.y >= max_y - 1 - rings
+}
+
+/// Used to get every cell of a path, from its corners
+fn trace(corners: &[Position]) -> Vec<Position> {
+    let mut cells: Vec<Position> = corners.iter().take(1).cloned().collect();
+    for corner in corners.iter().skip(1) {
+        while let Some(last) = cells.last().filter(|last| *last != corner) {
+            let step = Position::new(last.x + (corner.x - last.x).signum(), last.y + (corner.y - last.y).signum());
+            cells.push(step);
+        }
+    }
+    cells
+}
+
+/// Walls that appear, move and close in on the snakes while the game is played
+///
+/// Every cell that becomes a wall is warned about a number of ticks before
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Obstacles {
+    /// The number of ticks between every new obstacle, if they appear
+    spawn_every: Option<usize>,
+    /// The number of moving obstacles
+    moving: usize,
+    /// The number of ticks between every time the arena shrinks, if it does
+    shrink_every: Option<usize>,
+    warning: usize,
+    pending: Vec<Pending>,
+    movers: Vec<Mover>,
+    /// Every cell of the paths the movers from the level go along
+    paths: Vec<Vec<Position>>,
+    /// The number of rings of the arena that have been closed
+    rings: usize,
+}
+
+impl Obstacles {
+    /// Used to parse a list of obstacles separated by commas,
+    /// from spawn:N, moving:N, shrink:N and warning:N
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut obstacles = Obstacles {
+            warning: DEFAULT_WARNING,
+            ..Default::default()
+        };
+        for part in text.split(',') {
+            let (name, value) = part
+                .split_once(':')
+                .ok_or_else(|| format!("invalid obstacle {:?}, expected NAME:N", part))?;
+            let value: usize = value
+                .parse()
+                .map_err(|_| format!("invalid number {:?} for {}", value, name))?;
+            match name {
+                "spawn" | "shrink" if value == 0 => return Err(format!("{} needs a number above 0", name)),
+                "spawn" => obstacles.spawn_every = Some(value),
+                "moving" => obstacles.moving = value,
+                "shrink" => obstacles.shrink_every = Some(value),
+                "warning" => obstacles.warning = value,
+                _ => {
+                    return Err(format!(
+                        "unknown obstacle {:?}, expected spawn, moving, shrink or warning",
+                        name
+                    ))
+                }
+            }
+        }
+        Ok(obstacles)
+    }
+
+    /// Used to add a moving obstacle for every path of the board, which starts at its first corner
+    pub(crate) fn with_paths(mut self, paths: &[Vec<Position>]) -> Self {
+        for corners in paths {
+            self.movers.push(Mover {
+                pos: corners[0].clone(),
+                route: Route::Path {
+                    path: self.paths.len(),
+                    at: 0,
+                    back: false,
+                },
+            });
+            self.paths.push(trace(corners));
+        }
+        self
+    }
+
+    /// Used to get the cells that are about to become walls, and where the moving obstacles go next
+    pub fn warnings(&self) -> Vec<Position> {
+        let pending = self.pending.iter().map(|pending| pending.pos.clone());
+        let ahead = self.movers.iter().filter_map(|mover| mover.ahead(&self.paths)).map(|(pos, _)| pos);
+        pending.chain(ahead).collect()
+    }
+
+    /// Moves the obstacles forward to the tick, and warns about new ones
+    /// Returns the cells that became walls, where snakes and fruit have to go
+    pub(crate) fn update(
+        &mut self,
+        tick: usize,
+        board: &mut Board,
+        occupancy: &Occupancy,
+        free: &mut FreeCells,
+        heads: &[Position],
+        rng: &mut impl Rng,
+    ) -> Vec<Position> {
+        if tick == 1 {
+            for _ in 0..self.moving {
+                let dirr = [Directions::UP, Directions::DOWN, Directions::LEFT, Directions::RIGHT]
+                    .choose(rng)
+                    .cloned();
+                self.warn(tick, board, free, heads, dirr, rng);
+            }
+        }
+        if self.spawn_every.is_some_and(|every| tick.is_multiple_of(every)) {
+            self.warn(tick, board, free, heads, None, rng);
+        }
+        if self.shrink_every.is_some_and(|every| tick.is_multiple_of(every)) {
+            self.shrink(tick, board);
+            // Stopped for good, so they do not leave a hole in the closed rings
+            let rings = self.rings;
+            self.movers.retain(|mover| !is_closed(&mover.pos, rings, board));
+        }
+
+        if tick.is_multiple_of(MOVE_EVERY) {
+            for index in 0..self.movers.len() {
+                self.step(index, board, occupancy, free);
+            }
+        }
+
+        let (solid, pending) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition::<Vec<_>, _>(|pending| pending.solid_at <= tick);
+        self.pending = pending;
+        if !solid.is_empty() {
+            free.walls_changed();
+        }
+        for pending in &solid {
+            board[&pending.pos] = Items::WALL;
+            free.update(&pending.pos, board, occupancy);
+            if let Some(dirr) = &pending.moving {
+                self.movers.push(Mover {
+                    pos: pending.pos.clone(),
+                    route: Route::Bounce(dirr.clone()),
+                });
+            }
+        }
+        solid.into_iter().map(|pending| pending.pos).collect()
+    }
+
+    /// Used to warn about a new obstacle on a free cell away from the heads
+    fn warn(
+        &mut self,
+        tick: usize,
+        board: &Board,
+        free: &mut FreeCells,
+        heads: &[Position],
+        moving: Option<Directions>,
+        rng: &mut impl Rng,
+    ) {
+        let cells: Vec<&Position> = free
+            .reachable(board, heads)
+            .filter(|pos| {
+                !self.is_pending(pos)
+                    && heads
+                        .iter()
+                        .all(|head| pos.x.abs_diff(head.x) + pos.y.abs_diff(head.y) >= SPAWN_DISTANCE)
+            })
+            .collect();
+        if let Some(pos) = cells.choose(rng) {
+            self.pending.push(Pending {
+                pos: (*pos).clone(),
+                solid_at: tick + self.warning,
+                moving,
+            });
+        }
+    }
+
+    /// Used to close the next ring of the arena, as long as there is room left inside it
+    /// Portals on the ring stay open
+    fn shrink(&mut self, tick: usize, board: &Board) {
+        let (max_x, max_y) = board.get_max_size();
+        let ring = self.rings + 1;
+        if max_x.min(max_y) < 2 * (ring + 1) + board::MIN_SIZE - 2 {
+            return;
+        }
+        self.rings = ring;
+        for y in ring..max_y - ring {
+            for x in ring..max_x - ring {
+                let pos = Position::new(x as isize, y as isize);
+                let edge = x == ring || y == ring || x == max_x - 1 - ring || y == max_y - 1 - ring;
+                let open = !board.check_position(&pos, &Items::WALL) && board.portal(&pos).is_none();
+                if edge && open && !self.is_pending(&pos) {
+                    self.pending.push(Pending {
+                        pos,
+                        solid_at: tick + self.warning,
+                        moving: None,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Used to move a moving obstacle one step, turning around at walls or the ends of its path,
+    /// and waiting while a snake, a fruit, a warning or a closed ring is in the way
+    fn step(&mut self, index: usize, board: &mut Board, occupancy: &Occupancy, free: &mut FreeCells) {
+        let mover = &self.movers[index];
+        let Some((mut next, mut route)) = mover.ahead(&self.paths) else {
+            return;
+        };
+        if let Route::Bounce(dirr) = &route {
+            let blocked = |pos: &Position| !board.contains(pos) || board.check_position(pos, &Items::WALL);
+            if blocked(&next) {
+                let dirr = opposite(dirr);
+                next = mover.pos.step(&dirr);
+                if blocked(&next) {
+                    return;
+                }
+                route = Route::Bounce(dirr);
+            }
+        }
+        if !board.check_position(&next, &Items::EMPTY)
+            || !occupancy.is_empty(&next)
+            || self.is_pending(&next)
+            || is_closed(&next, self.rings, board)
+        {
+            return;
+        }
+        board[&mover.pos] = Items::EMPTY;
+        board[&next] = Items::WALL;
+        free.update(&mover.pos, board, occupancy);
+        free.update(&next, board, occupancy);
+        free.walls_changed();
+        self.movers[index] = Mover { pos: next, route };
+    }
+
+    fn is_pending(&self, pos: &Position) -> bool {
+        self.pending.iter().any(|pending| pending.pos == *pos)
+    }
+
+}
+
+#[cfg(test)]
+mod obstacles_test {
+    use rand::{prelude::StdRng, SeedableRng};
+
+    use crate::obstacles::*;
+
+    #[test]
+    fn test_parse() {
+        let obstacles = Obstacles::parse("spawn:20,moving:2,shrink:100,warning:5").unwrap();
+        assert_eq!(Some(20), obstacles.spawn_every);
+        assert_eq!(2, obstacles.moving);
+        assert_eq!(Some(100), obstacles.shrink_every);
+        assert_eq!(5, obstacles.warning);
+        assert!(Obstacles::parse("spawn").is_err());
+        assert!(Obstacles::parse("spawn:0").is_err());
+        assert!(Obstacles::parse("lava:3").is_err());
+    }
+
+    #[test]
+    fn test_update() {
+        let mut board = Board::new(16, 16, false);
+        let occupancy = Occupancy::new(board.get_max_size(), &[]);
+        let heads = [Position::new(8, 8)];
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut obstacles = Obstacles::parse("spawn:2,shrink:4,moving:1,warning:3").unwrap();
+
+        let mut free = FreeCells::new(&board, &occupancy);
+        let mut update =
+            |tick, board: &mut Board| obstacles.update(tick, board, &occupancy, &mut free, &heads, &mut rng);
+        assert!(update(1, &mut board).is_empty());
+        assert!(update(2, &mut board).is_empty());
+        // The moving obstacle, and then the first one that appeared
+        let solid = update(4, &mut board);
+        assert_eq!(1, solid.len());
+        assert!(board.check_position(&solid[0], &Items::WALL));
+        let solid = update(5, &mut board);
+        assert_eq!(1, solid.len());
+        assert!(board.check_position(&solid[0], &Items::WALL));
+        // The first ring of the arena closes
+        let solid = update(7, &mut board);
+        assert!(solid.contains(&Position::new(1, 1)));
+        assert!(solid.contains(&Position::new(14, 9)));
+    }
+
+    #[test]
+    fn test_paths() {
+        let corners = vec![Position::new(2, 2), Position::new(2, 4), Position::new(4, 4)];
+        assert_eq!(5, trace(&corners).len());
+        let mut board = Board::new(8, 8, false).with_paths(vec![corners]);
+        let occupancy = Occupancy::new(board.get_max_size(), &[]);
+        let mut free = FreeCells::new(&board, &occupancy);
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut obstacles = Obstacles::default().with_paths(board.paths());
+
+        // To the end of the path, and back again
+        let route = [(2, 3), (2, 4), (3, 4), (4, 4), (3, 4), (2, 4), (2, 3), (2, 2), (2, 3)];
+        for (step, (x, y)) in route.into_iter().enumerate() {
+            let pos = Position::new(x, y);
+            assert_eq!(vec![pos.clone()], obstacles.warnings());
+            obstacles.update((step + 1) * MOVE_EVERY, &mut board, &occupancy, &mut free, &[], &mut rng);
+            assert!(board.check_position(&pos, &Items::WALL));
+            assert!(!free.contains(&pos));
+        }
+    }
+
+    #[test]
+    fn test_closed_ring() {
+        // A wall on the first ring stays there once the ring closes
+        let corners = vec![Position::new(1, 5), Position::new(1, 10)];
+        let mut board = Board::new(16, 16, false).with_paths(vec![corners]);
+        let occupancy = Occupancy::new(board.get_max_size(), &[]);
+        let mut free = FreeCells::new(&board, &occupancy);
+        let heads = [Position::new(8, 8)];
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut obstacles = Obstacles::parse("shrink:100,warning:2").unwrap().with_paths(board.paths());
+        for tick in 100..=104 {
+            obstacles.update(tick, &mut board, &occupancy, &mut free, &heads, &mut rng);
+        }
+        assert!(obstacles.warnings().is_empty());
+        for y in 1..15 {
+            assert!(board.check_position(&Position::new(1, y), &Items::WALL));
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use crate::snake::{Position, Snake};
+
+/// The most snakes there can be in a game, one for each bit of a cell
+pub const MAX_PLAYERS: usize = 64;
+
+/// Which snakes are on every cell of the board, derived from the bodies of the snakes
+///
+/// Every cell is a set of snakes, one bit each, so teammates can share a cell
+#[derive(Clone, Debug, PartialEq)]
+pub struct Occupancy {
+    cells: Vec<u64>,
+    width: usize,
+    /// The team of every snake
+    teams: Vec<usize>,
+}
+
+impl Occupancy {
+    /// Creates a new [`Occupancy`] of the given size, with the bodies of the snakes on it
+    pub fn new(size: (usize, usize), players: &[Snake]) -> Self {
+        assert!(players.len() <= MAX_PLAYERS, "There can only be {} snakes", MAX_PLAYERS);
+        let mut occupancy = Occupancy {
+            cells: vec![0; size.0 * size.1],
+            width: size.0,
+            teams: players.iter().map(|player| player.get_team()).collect(),
+        };
+        for (index, player) in players.iter().enumerate() {
+            for pos in player.get_tail() {
+                occupancy.add(pos, index);
+            }
+        }
+        occupancy
+    }
+
+    fn index(&self, pos: &Position) -> usize {
+        pos.y as usize * self.width + pos.x as usize
+    }
+
+    /// Used to put a part of the snake at index player on a position
+    pub fn add(&mut self, pos: &Position, player: usize) {
+        let index = self.index(pos);
+        self.cells[index] |= 1 << player;
+    }
+
+    /// Used to take a part of the snake at index player off a position
+    pub fn remove(&mut self, pos: &Position, player: usize) {
+        let index = self.index(pos);
+        self.cells[index] &= !(1 << player);
+    }
+
+    /// Checks if no snake is on a position
+    pub fn is_empty(&self, pos: &Position) -> bool {
+        self.cells[self.index(pos)] == 0
+    }
+
+    /// Checks if the snake at index player is on a position
+    pub fn contains(&self, pos: &Position, player: usize) -> bool {
+        self.cells[self.index(pos)] & (1 << player) != 0
+    }
+
+    /// Checks if every snake on a position plays for the team
+    pub fn only_team(&self, pos: &Position, team: usize) -> bool {
+        self.players(pos).all(|player| self.teams[player] == team)
+    }
+
+    /// Used to get the indices of the snakes on a position
+    pub fn players(&self, pos: &Position) -> impl Iterator<Item = usize> {
+        let cell = self.cells[self.index(pos)];
+        (0..self.teams.len()).filter(move |player| cell & (1 << player) != 0)
+    }
+}
+
+#[cfg(test)]
+mod occupancy_test {
+    use crate::{occupancy::*, team::Team};
+
+    #[test]
+    fn test_occupancy() {
+        let team = Team::new(0, false).unwrap();
+        let players = vec![Snake::new(Position::new(2, 2), &team), Snake::new(Position::new(3, 3), &team)];
+        let mut occupancy = Occupancy::new((8, 8), &players);
+        let pos = Position::new(2, 2);
+        assert!(occupancy.contains(&pos, 0));
+        assert!(!occupancy.contains(&pos, 1));
+        assert!(occupancy.is_empty(&Position::new(1, 1)));
+
+        occupancy.add(&pos, 1);
+        assert_eq!(vec![0, 1], occupancy.players(&pos).collect::<Vec<_>>());
+        assert!(occupancy.only_team(&pos, 0));
+        assert!(!occupancy.only_team(&pos, 1));
+        occupancy.remove(&pos, 0);
+        occupancy.remove(&pos, 1);
+        assert!(occupancy.is_empty(&pos));
+    }
+}
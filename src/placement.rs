@@ -0,0 +1,307 @@
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{board::Board, occupancy::Occupancy, snake::Position, Items};
+
+/// The distance from every head used by away, when no distance is given
+pub const DEFAULT_DISTANCE: usize = 4;
+
+/// A rectangle of cells on the board
+#[derive(Clone, Debug, PartialEq)]
+pub struct Zone {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Zone {
+    fn contains(&self, pos: &Position) -> bool {
+        let (x, y) = (pos.x as usize, pos.y as usize);
+        x >= self.x && y >= self.y && x - self.x < self.width && y - self.y < self.height
+    }
+}
+
+/// Where new fruit is placed, among the free cells
+///
+/// When no free cell follows the policy, the fruit is placed on any free cell
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Placement {
+    /// Any free cell, all equally likely
+    #[default]
+    Uniform,
+    /// A free cell at least this many steps from every head
+    AwayFromHeads(usize),
+    /// A free cell inside one of the zones
+    Zones(Vec<Zone>),
+}
+
+/// The number of random free cells looked at for every fruit, before looking through all of them
+const SAMPLES: usize = 16;
+
+/// The cells that have nothing on them, kept up to date as the game changes,
+/// so that placing something does not have to look at the whole board
+#[derive(Clone, Debug)]
+pub struct FreeCells {
+    cells: Vec<Position>,
+    /// Where every cell of the board is in cells, row by row, if it is free
+    index: Vec<Option<usize>>,
+    width: usize,
+    /// The cells that can be reached from the heads, until the walls change
+    reached: Option<Vec<Vec<bool>>>,
+}
+
+impl FreeCells {
+    /// Creates a new [`FreeCells`], which is the only time the whole board is looked at
+    pub fn new(board: &Board, occupancy: &Occupancy) -> Self {
+        let (max_x, max_y) = board.get_max_size();
+        let mut free = FreeCells {
+            cells: vec![],
+            index: vec![None; max_x * max_y],
+            width: max_x,
+            reached: None,
+        };
+        for y in 0..max_y {
+            for x in 0..max_x {
+                free.update(&Position::new(x as isize, y as isize), board, occupancy);
+            }
+        }
+        free
+    }
+
+    /// Used to look at a cell again, after something came onto it or left it
+    pub fn update(&mut self, pos: &Position, board: &Board, occupancy: &Occupancy) {
+        if board.check_position(pos, &Items::EMPTY) && occupancy.is_empty(pos) {
+            self.insert(pos);
+        } else {
+            self.remove(pos);
+        }
+    }
+
+    /// Used to add a cell that was left empty
+    pub(crate) fn insert(&mut self, pos: &Position) {
+        let key = self.key(pos);
+        if self.index[key].is_none() {
+            self.index[key] = Some(self.cells.len());
+            self.cells.push(pos.clone());
+        }
+    }
+
+    /// Used to take away a cell that something came onto
+    pub(crate) fn remove(&mut self, pos: &Position) {
+        let key = self.key(pos);
+        let Some(at) = self.index[key].take() else {
+            return;
+        };
+        self.cells.swap_remove(at);
+        if let Some(moved) = self.cells.get(at) {
+            let moved = self.key(moved);
+            self.index[moved] = Some(at);
+        }
+    }
+
+    pub fn contains(&self, pos: &Position) -> bool {
+        self.index[self.key(pos)].is_some()
+    }
+
+    /// Used to forget which cells can be reached, after a wall came or went,
+    /// or the heads moved on a board with one-way cells
+    pub fn walls_changed(&mut self) {
+        self.reached = None;
+    }
+
+    /// Used to get every free cell that can be reached from the heads
+    pub fn reachable<'a>(&'a mut self, board: &Board, heads: &[Position]) -> impl Iterator<Item = &'a Position> {
+        let reached = self.reached.get_or_insert_with(|| board.reachable(heads));
+        self.cells.iter().filter(|pos| reached[pos.y as usize][pos.x as usize])
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    fn key(&self, pos: &Position) -> usize {
+        pos.y as usize * self.width + pos.x as usize
+    }
+}
+
+impl Placement {
+    /// Used to parse a policy, either uniform, away, away:N, or zone:X,Y,W,H with more zones after a ;
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let (name, value) = text.split_once(':').unwrap_or((text, ""));
+        match (name, value) {
+            ("uniform", "") => Ok(Placement::Uniform),
+            ("away", "") => Ok(Placement::AwayFromHeads(DEFAULT_DISTANCE)),
+            ("away", distance) => distance
+                .parse()
+                .map(Placement::AwayFromHeads)
+                .map_err(|_| format!("invalid distance {:?}", distance)),
+            ("zone", zones) => zones.split(';').map(parse_zone).collect::<Result<_, _>>().map(Placement::Zones),
+            _ => Err(format!(
+                "unknown placement {:?}, expected uniform, away, away:N or zone:X,Y,W,H",
+                text
+            )),
+        }
+    }
+
+    /// Used to check if a free cell follows the policy
+    fn prefers(&self, pos: &Position, heads: &[Position]) -> bool {
+        match self {
+            Placement::Uniform => true,
+            Placement::AwayFromHeads(distance) => heads
+                .iter()
+                .all(|head| pos.x.abs_diff(head.x) + pos.y.abs_diff(head.y) >= *distance),
+            Placement::Zones(zones) => zones.iter().any(|zone| zone.contains(pos)),
+        }
+    }
+
+    /// Used to choose where the next fruit goes
+    /// Returns None if the heads can not reach any free cell, and the board is full
+    pub fn choose(
+        &self,
+        free: &mut FreeCells,
+        board: &Board,
+        heads: &[Position],
+        rng: &mut impl Rng,
+    ) -> Option<Position> {
+        self.choose_many(free, board, heads, 1, rng).pop()
+    }
+
+    /// Used to choose where a number of fruits go, all on different cells
+    ///
+    /// Random free cells are tried first, and all of them are only looked through
+    /// when too few of those could be reached or follow the policy
+    /// Returns fewer positions than asked for if there are not enough free cells
+    pub fn choose_many(
+        &self,
+        free: &mut FreeCells,
+        board: &Board,
+        heads: &[Position],
+        count: usize,
+        rng: &mut impl Rng,
+    ) -> Vec<Position> {
+        let reached = free.reached.get_or_insert_with(|| board.reachable(heads));
+        let wanted = |pos: &Position| reached[pos.y as usize][pos.x as usize] && self.prefers(pos, heads);
+        let mut chosen: Vec<Position> = vec![];
+        for _ in 0..SAMPLES * count {
+            let Some(pos) = free.cells.choose(rng).filter(|pos| wanted(pos) && !chosen.contains(pos)) else {
+                continue;
+            };
+            chosen.push(pos.clone());
+            if chosen.len() == count {
+                return chosen;
+            }
+        }
+
+        let (mut preferred, mut rest): (Vec<Position>, Vec<Position>) = free
+            .cells
+            .iter()
+            .filter(|pos| reached[pos.y as usize][pos.x as usize] && !chosen.contains(pos))
+            .cloned()
+            .partition(|pos| self.prefers(pos, heads));
+        let missing = count - chosen.len();
+        chosen.extend_from_slice(preferred.partial_shuffle(rng, missing).0);
+        let missing = count - chosen.len();
+        chosen.extend_from_slice(rest.partial_shuffle(rng, missing).0);
+        chosen
+    }
+}
+
+fn parse_zone(text: &str) -> Result<Zone, String> {
+    let values: Vec<usize> = text
+        .split(',')
+        .map(|value| value.trim().parse())
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("invalid zone {:?}, expected X,Y,W,H", text))?;
+    match values[..] {
+        [x, y, width, height] => Ok(Zone { x, y, width, height }),
+        _ => Err(format!("invalid zone {:?}, expected X,Y,W,H", text)),
+    }
+}
+
+#[cfg(test)]
+mod placement_test {
+    use rand::{prelude::StdRng, SeedableRng};
+
+    use crate::{placement::*, snake::Snake, team::Team};
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Ok(Placement::Uniform), Placement::parse("uniform"));
+        assert_eq!(Ok(Placement::AwayFromHeads(DEFAULT_DISTANCE)), Placement::parse("away"));
+        assert_eq!(Ok(Placement::AwayFromHeads(6)), Placement::parse("away:6"));
+        assert_eq!(
+            Ok(Placement::Zones(vec![
+                Zone { x: 1, y: 2, width: 3, height: 4 },
+                Zone { x: 5, y: 5, width: 1, height: 1 }
+            ])),
+            Placement::parse("zone:1,2,3,4;5,5,1,1")
+        );
+        assert!(Placement::parse("zone:1,2").is_err());
+        assert!(Placement::parse("nearby").is_err());
+    }
+
+    #[test]
+    fn test_choose() {
+        let board = Board::new(8, 8, false);
+        let occupancy = Occupancy::new(board.get_max_size(), &[]);
+        let mut free = FreeCells::new(&board, &occupancy);
+        let mut rng = StdRng::seed_from_u64(0);
+        let heads = [Position::new(1, 1)];
+        assert_eq!(36, free.reachable(&board, &heads).count());
+
+        for _ in 0..20 {
+            let pos = Placement::AwayFromHeads(10).choose(&mut free, &board, &heads, &mut rng).unwrap();
+            assert_eq!(Position::new(6, 6), pos);
+            let zone = Placement::Zones(vec![Zone { x: 2, y: 3, width: 1, height: 1 }]);
+            assert_eq!(Some(Position::new(2, 3)), zone.choose(&mut free, &board, &heads, &mut rng));
+        }
+
+        let mut board = Board::new(3, 3, false);
+        board[Position::new(1, 1)] = Items::FRUIT;
+        let occupancy = Occupancy::new(board.get_max_size(), &[]);
+        let mut free = FreeCells::new(&board, &occupancy);
+        assert_eq!(None, Placement::Uniform.choose(&mut free, &board, &heads, &mut rng));
+
+        // The cell in the zone first, then any other free cells
+        let board = Board::new(5, 5, false);
+        let occupancy = Occupancy::new(board.get_max_size(), &[]);
+        let mut free = FreeCells::new(&board, &occupancy);
+        let zone = Placement::Zones(vec![Zone { x: 2, y: 2, width: 1, height: 1 }]);
+        let chosen = zone.choose_many(&mut free, &board, &heads, 4, &mut rng);
+        assert_eq!(Position::new(2, 2), chosen[0]);
+        assert_eq!(4, chosen.len());
+        assert!(chosen[1..].iter().all(|pos| *pos != chosen[0]));
+        assert_eq!(9, Placement::Uniform.choose_many(&mut free, &board, &heads, 20, &mut rng).len());
+
+        // Zones reaching past the end of the numbers
+        let zone = Zone { x: 1, y: 1, width: usize::MAX, height: usize::MAX };
+        assert!(zone.contains(&Position::new(4, 4)));
+        assert!(!zone.contains(&Position::new(0, 4)));
+    }
+
+    #[test]
+    fn test_free_cells() {
+        let mut board = Board::new(5, 5, false);
+        let team = Team::new(0, true).unwrap();
+        let snake = Snake::new(Position::new(2, 2), &team);
+        let mut occupancy = Occupancy::new(board.get_max_size(), std::slice::from_ref(&snake));
+        let mut free = FreeCells::new(&board, &occupancy);
+        assert_eq!(8, free.len());
+
+        board[Position::new(1, 1)] = Items::FRUIT;
+        free.update(&Position::new(1, 1), &board, &occupancy);
+        occupancy.remove(&Position::new(2, 2), 0);
+        free.update(&Position::new(2, 2), &board, &occupancy);
+        let mut cells: Vec<Position> = free.reachable(&board, &[Position::new(2, 2)]).cloned().collect();
+        cells.sort_by_key(|pos| (pos.y, pos.x));
+        let mut expected = FreeCells::new(&board, &occupancy).cells;
+        expected.sort_by_key(|pos| (pos.y, pos.x));
+        assert_eq!(expected, cells);
+        assert!(!cells.contains(&Position::new(1, 1)));
+        assert!(cells.contains(&Position::new(2, 2)));
+    }
+}
@@ -0,0 +1,228 @@
+use snake::{controller::helper_enums::Directions, Game, Items, Position};
+use tui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Span, Spans},
+    widgets::Widget,
+};
+
+use crate::theme::{Segment, Theme};
+
+/// What is drawn instead of a fruit in its last ticks, before it goes away
+const COUNTDOWN: [&str; 10] = [" 0", " 1", " 2", " 3", " 4", " 5", " 6", " 7", " 8", " 9"];
+
+/// Used to get the side of a cell that a neighbouring cell is on, even across the edges of the board
+fn side(from: &Position, to: &Position) -> Directions {
+    let wrap = |delta: isize| if delta.abs() > 1 { -delta.signum() } else { delta };
+    match (wrap(to.x - from.x), wrap(to.y - from.y)) {
+        (1, _) => Directions::RIGHT,
+        (-1, _) => Directions::LEFT,
+        (_, 1) => Directions::DOWN,
+        _ => Directions::UP,
+    }
+}
+
+/// The part of the board that is shown, in cells
+#[derive(Clone, Debug, PartialEq)]
+pub struct View {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl View {
+    /// Used to show the whole board
+    pub fn whole(board: (usize, usize)) -> Self {
+        View {
+            x: 0,
+            y: 0,
+            width: board.0,
+            height: board.1,
+        }
+    }
+
+    /// Used to get a camera of the given size following a position, which stays inside the board
+    pub fn camera(pos: &Position, board: (usize, usize), size: (usize, usize)) -> Self {
+        let width = size.0.min(board.0);
+        let height = size.1.min(board.1);
+        View {
+            x: (pos.x.max(0) as usize).saturating_sub(width / 2).min(board.0 - width),
+            y: (pos.y.max(0) as usize).saturating_sub(height / 2).min(board.1 - height),
+            width,
+            height,
+        }
+    }
+
+    /// Used to get where a position is in the view, if it is in it
+    fn local(&self, pos: &Position) -> Option<(usize, usize)> {
+        let (x, y) = (pos.x as usize, pos.y as usize);
+        if pos.x < 0 || pos.y < 0 || x < self.x || y < self.y || x >= self.x + self.width || y >= self.y + self.height {
+            None
+        } else {
+            Some((x - self.x, y - self.y))
+        }
+    }
+}
+
+/// Draws the part of the board in the view straight into the buffer, with the snakes and the fruits on top of it
+///
+/// Every cell in the view is drawn on every frame, and the terminal then only writes the ones that differ from the last
+pub struct BoardView<'a> {
+    game: &'a Game,
+    theme: &'a Theme,
+    view: View,
+}
+
+impl<'a> BoardView<'a> {
+    pub fn new(game: &'a Game, theme: &'a Theme, view: View) -> Self {
+        BoardView { game, theme, view }
+    }
+}
+
+impl Widget for BoardView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let (game, theme, view) = (self.game, self.theme, &self.view);
+        let width = view.width.min(area.width as usize / 2);
+        let height = view.height.min(area.height as usize);
+        let mut draw = |(x, y): (usize, usize), (glyph, style): (&str, Style)| {
+            if x < width && y < height {
+                buf.set_stringn(area.x + 2 * x as u16, area.y + y as u16, glyph, 2, style);
+            }
+        };
+
+        for (y, row) in game.board().get_vec()[view.y..view.y + height].iter().enumerate() {
+            for (x, item) in row[view.x..view.x + width].iter().enumerate() {
+                if *item == Items::EMPTY {
+                    let pos = Position::new((view.x + x) as isize, (view.y + y) as isize);
+                    draw((x, y), theme.terrain(game.board().terrain(&pos)));
+                } else {
+                    draw((x, y), theme.cell(item));
+                }
+            }
+        }
+        for pos in game.warnings() {
+            if let Some(local) = view.local(&pos) {
+                if game.board().check_position(&pos, &Items::EMPTY) {
+                    draw(local, theme.warning());
+                }
+            }
+        }
+        for player in game.players() {
+            let tail = player.get_tail();
+            for (index, pos) in tail.iter().enumerate().rev() {
+                let Some(local) = view.local(pos) else {
+                    continue;
+                };
+                let segment = if index == 0 {
+                    Segment::Head
+                } else if index == tail.len() - 1 {
+                    Segment::Tail
+                } else {
+                    Segment::Body(side(pos, &tail[index - 1]), side(pos, &tail[index + 1]))
+                };
+                draw(local, theme.snake(player.get_team(), player.get_player(), &segment));
+            }
+        }
+        for (fruit_pos, fruit_type) in game.fruits() {
+            if let Some(local) = view.local(fruit_pos) {
+                match game.countdown(fruit_pos) {
+                    Some(left) if left < COUNTDOWN.len() => {
+                        draw(local, (COUNTDOWN[left], theme.cell(fruit_type).1.add_modifier(Modifier::BOLD)))
+                    }
+                    _ => draw(local, theme.cell(fruit_type)),
+                }
+            }
+        }
+    }
+}
+
+/// Used to draw the whole board shrunk to fit the size, with one column for every block of cells
+///
+/// The most important thing in a block is drawn: a head, a snake, a fruit, a wall, or nothing
+pub fn minimap(game: &Game, theme: &Theme, size: (usize, usize)) -> Vec<Spans<'static>> {
+    let (max_x, max_y) = game.board().get_max_size();
+    if size.0 == 0 || size.1 == 0 {
+        return vec![];
+    }
+    let scale = max_x.div_ceil(size.0).max(max_y.div_ceil(size.1)).max(1);
+    let (width, height) = (max_x.div_ceil(scale), max_y.div_ceil(scale));
+
+    let mut cells = vec![vec![(0, " ", Style::default()); width]; height];
+    let mut mark = |pos: &Position, rank: u8, glyph: &'static str, style: Style| {
+        let cell = &mut cells[pos.y as usize / scale][pos.x as usize / scale];
+        if rank > cell.0 {
+            *cell = (rank, glyph, style);
+        }
+    };
+    for (y_pos, row) in game.board().get_vec().iter().enumerate() {
+        for (x_pos, item) in row.iter().enumerate() {
+            if *item == Items::WALL {
+                mark(&Position::new(x_pos as isize, y_pos as isize), 1, "#", theme.cell(item).1);
+            }
+        }
+    }
+    for (pos, fruit) in game.fruits() {
+        mark(pos, 2, "*", theme.cell(fruit).1);
+    }
+    for player in game.players() {
+        for pos in player.get_tail() {
+            mark(pos, 3, "o", theme.snake(player.get_team(), player.get_player(), &Segment::Tail).1);
+        }
+        if let Some(head) = player.get_tail().front() {
+            mark(head, 4, "@", theme.snake(player.get_team(), player.get_player(), &Segment::Head).1);
+        }
+    }
+    cells
+        .into_iter()
+        .map(|row| Spans::from(row.into_iter().map(|(_, glyph, style)| Span::styled(glyph, style)).collect::<Vec<_>>()))
+        .collect()
+}
+
+#[cfg(test)]
+mod render_test {
+    use crate::render::*;
+
+    fn make_game(size: (usize, usize)) -> Game {
+        let team = snake::Team::new(0, true).unwrap();
+        let player = snake::Snake::new(Position::new(size.0 as isize / 2, size.1 as isize / 2), &team);
+        Game::seeded(snake::Board::new(size.0, size.1, false), vec![player], vec![team], 0)
+    }
+
+    #[test]
+    fn test_board_view() {
+        let game = make_game((8, 6));
+        let theme = Theme::default();
+        let area = Rect::new(0, 0, 16, 6);
+        let mut buf = Buffer::empty(area);
+        BoardView::new(&game, &theme, View::whole((8, 6))).render(area, &mut buf);
+        assert_eq!("@", buf.get(9, 3).symbol);
+        assert_eq!("W", buf.get(1, 0).symbol);
+
+        // Only the part of the board in the view is drawn, from the top left of the area
+        let mut buf = Buffer::empty(area);
+        let view = View { x: 3, y: 2, width: 2, height: 2 };
+        BoardView::new(&game, &theme, view).render(area, &mut buf);
+        assert_eq!("@", buf.get(3, 1).symbol);
+        assert_eq!(" ", buf.get(5, 0).symbol);
+
+        // The ticks left of a fruit that goes away
+        let game = make_game((8, 6)).with_fruit_rules(snake::fruit::FruitRules::parse("expire:5").unwrap());
+        let mut buf = Buffer::empty(area);
+        BoardView::new(&game, &theme, View::whole((8, 6))).render(area, &mut buf);
+        let (pos, _) = &game.fruits()[0];
+        assert_eq!("5", buf.get(2 * pos.x as u16 + 1, pos.y as u16).symbol);
+    }
+
+    #[test]
+    fn test_camera() {
+        let board = (100, 50);
+        let view = View::camera(&Position::new(50, 25), board, (20, 10));
+        assert_eq!(View { x: 40, y: 20, width: 20, height: 10 }, view);
+        let view = View::camera(&Position::new(2, 48), board, (20, 10));
+        assert_eq!(View { x: 0, y: 40, width: 20, height: 10 }, view);
+        let view = View::camera(&Position::new(2, 2), (10, 10), (20, 20));
+        assert_eq!(View::whole((10, 10)), view);
+    }
+}
@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+
+use std::{collections::BTreeMap, env, fs, io, path::{Path, PathBuf}, time::Duration};
+
+/// The name of the file the high scores are kept in, in the home directory
+pub const FILE_NAME: &str = ".snake-scores.json";
+
+/// The best result on a level
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct Record {
+    /// The most fruits eaten by a single snake
+    pub score: usize,
+    /// The fastest time a snake filled the board, in milliseconds
+    pub fastest_ms: Option<u64>,
+}
+
+impl Record {
+    /// Used to get the fastest time the board was filled, if it has been
+    pub fn fastest(&self) -> Option<Duration> {
+        self.fastest_ms.map(Duration::from_millis)
+    }
+}
+
+/// The best results, by level
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct HighScores {
+    levels: BTreeMap<String, Record>,
+}
+
+impl HighScores {
+    /// Used to get where the high scores are kept by default, if there is a home directory
+    pub fn default_path() -> Option<PathBuf> {
+        env::var_os("HOME").map(|home| Path::new(&home).join(FILE_NAME))
+    }
+
+    /// Used to read the high scores from a file, where a missing file has no high scores
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Used to write the high scores to a file
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let text = serde_json::to_string_pretty(self).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        fs::write(path, text)
+    }
+
+    /// Used to get the best result on a level, if it has been played
+    pub fn get(&self, level: &str) -> Option<&Record> {
+        self.levels.get(level)
+    }
+
+    /// Used to add a score on a level
+    /// Returns true if it is a new high score
+    pub fn record(&mut self, level: &str, score: usize) -> bool {
+        if score <= self.levels.get(level).map_or(0, |record| record.score) {
+            return false;
+        }
+        self.levels.entry(level.to_string()).or_default().score = score;
+        true
+    }
+
+    /// Used to add the time it took to fill the board on a level
+    /// Returns true if it is the fastest time
+    pub fn complete(&mut self, level: &str, time: Duration) -> bool {
+        let record = self.levels.entry(level.to_string()).or_default();
+        let time = time.as_millis() as u64;
+        if record.fastest_ms.is_none_or(|fastest| time < fastest) {
+            record.fastest_ms = Some(time);
+            return true;
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod scores_test {
+    use crate::scores::*;
+
+    #[test]
+    fn test_record() {
+        let mut scores = HighScores::default();
+        assert_eq!(None, scores.get("classic"));
+        assert!(!scores.record("classic", 0));
+        assert_eq!(None, scores.get("classic"));
+        assert!(scores.record("classic", 3));
+        assert!(!scores.record("classic", 2));
+        assert!(scores.record("classic", 5));
+        assert_eq!(5, scores.get("classic").unwrap().score);
+        assert_eq!(None, scores.get("classic").unwrap().fastest());
+        assert!(scores.complete("classic", Duration::from_secs(90)));
+        assert!(!scores.complete("classic", Duration::from_secs(95)));
+        assert!(scores.complete("classic", Duration::from_secs(80)));
+        assert_eq!(Some(Duration::from_secs(80)), scores.get("classic").unwrap().fastest());
+        assert_eq!(5, scores.get("classic").unwrap().score);
+
+        let path = env::temp_dir().join(format!("snake-scores-{}.json", std::process::id()));
+        scores.save(&path).unwrap();
+        assert_eq!(scores, HighScores::load(&path).unwrap());
+        fs::remove_file(&path).unwrap();
+        assert_eq!(HighScores::default(), HighScores::load(&path).unwrap());
+    }
+}
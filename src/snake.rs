@@ -1,19 +1,17 @@
 use std::{collections::VecDeque, fmt::Display};
 
-use crossterm::event::Event;
-use tui::{
-    style::{Color, Style},
-    text::Span,
-};
+use serde::Serialize;
 
 use crate::{
     board::Board,
-    controller::helper_enums::{Directions, MoveOpt},
+    controller::helper_enums::Directions,
+    occupancy::Occupancy,
+    team::Team,
+    terrain::{Terrain, ICE_TICKS},
     Items,
-    consts::{SNEK, EMPTY}
 };
 
-#[derive(Clone, Debug, PartialEq, PartialOrd)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Serialize)]
 pub struct Position {
     pub x: isize,
     pub y: isize,
@@ -29,75 +27,115 @@ impl Position {
     pub fn new(x: isize, y: isize) -> Self {
         Position { x, y }
     }
+
+    /// Used to get the position next to this one, in the given direction
+    pub fn step(&self, dirr: &Directions) -> Position {
+        match dirr {
+            Directions::UP => Position::new(self.x, self.y - 1),
+            Directions::DOWN => Position::new(self.x, self.y + 1),
+            Directions::LEFT => Position::new(self.x - 1, self.y),
+            Directions::RIGHT => Position::new(self.x + 1, self.y),
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, PartialOrd)]
-pub enum Happen<T> {
+pub(crate) enum Happen<T> {
     Some(T),
     Break,
     None,
 }
 
-pub struct Snake<'a> {
+pub struct Snake {
     pos: Position,
     tail: VecDeque<Position>,
     size: usize,
-    keys: fn(Event) -> MoveOpt<Directions>,
     dirr: Directions,
-    snake_self: Items,
-    snake: Span<'a>,
+    /// The place of the snake among the players, which decides how it looks
+    player: usize,
     fruit: Items,
+    team: usize,
+    friendly_fire: bool,
+    alive: bool,
+    /// The number of ticks left before the snake can turn, after going onto ice
+    sliding: usize,
+    /// Set on every other tick in mud, when the snake does not move
+    stuck: bool,
+    /// The fruits eaten since the snake came into the game, which does not count food
+    eaten: usize,
 }
 
-impl<'a> Snake<'a> {
-    /// Creates a new [`Snake`].
-    pub fn new(
-        start_pos: Position,
-        snake_self: Items,
-        fruit: Items,
-        keys: fn(Event) -> MoveOpt<Directions>,
-    ) -> Self {
+impl Snake {
+    /// Creates a new [`Snake`], playing for the given team.
+    pub fn new(start_pos: Position, team: &Team) -> Self {
         let mut tail = VecDeque::new();
         tail.push_front(start_pos.clone());
-        let snake = match snake_self {
-            Items::SNAKE => (Span::styled(SNEK, Style::default().bg(Color::Green))),
-            Items::OSNAKE => (Span::styled(SNEK, Style::default().bg(Color::Yellow))),
-            _ => Span::from(EMPTY),
-        };
 
         Snake {
             pos: start_pos,
             tail,
             size: 4,
-            keys,
             dirr: Directions::LEFT,
-            snake_self,
-            snake,
-            fruit,
+            player: 0,
+            fruit: team.fruit().clone(),
+            team: team.get_id(),
+            friendly_fire: team.friendly_fire(),
+            alive: true,
+            sliding: 0,
+            stuck: false,
+            eaten: 0,
         }
     }
 
     pub fn get_back(&mut self) -> Option<Position> {
-        return if self.tail.len() > self.size {
-            Some(self.tail.pop_back().unwrap())
+        if self.tail.len() > self.size {
+            self.tail.pop_back()
         } else {
             None
-        };
+        }
     }
 
-    pub fn mover(&mut self, read: Event) {
-        let opt = self.keys;
-        let dirr = match read {
-            event => opt(event),
-        };
-        if let MoveOpt::Some(new_dirr) = dirr {
-            if self.dirr != opposite(&new_dirr) {
-                self.dirr = new_dirr
-            }
+    /// Turns the snake in a new direction, unless it would turn back on itself, or it is sliding on ice
+    pub fn turn(&mut self, new_dirr: Directions) {
+        if self.dirr != opposite(&new_dirr) && self.sliding == 0 {
+            self.dirr = new_dirr
         }
     }
 
+    /// Used to take the snake out of the game
+    pub fn kill(&mut self) {
+        self.alive = false;
+    }
+
+    /// Used to take the body off a dead snake, which is no longer on the board
+    pub(crate) fn take_tail(&mut self) -> VecDeque<Position> {
+        std::mem::take(&mut self.tail)
+    }
+
+    /// Used to bring a dead snake back at a position, as short as it was at the start
+    pub(crate) fn respawn(&mut self, pos: Position) {
+        self.pos = pos.clone();
+        self.tail = VecDeque::from([pos]);
+        self.size = 4;
+        self.dirr = Directions::LEFT;
+        self.alive = true;
+        self.sliding = 0;
+        self.stuck = false;
+        self.eaten = 0;
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.alive
+    }
+
     pub fn eat(&mut self) -> bool {
+        self.size += 1;
+        self.eaten += 1;
+        true
+    }
+
+    /// Used to grow from eating food left by a dead snake, which is not scored
+    fn grow(&mut self) -> bool {
         self.size += 1;
         true
     }
@@ -106,6 +144,10 @@ impl<'a> Snake<'a> {
         &self.fruit
     }
 
+    pub fn get_dirr(&self) -> Directions {
+        self.dirr.clone()
+    }
+
     pub fn get_pos(&self) -> Position {
         self.pos.clone()
     }
@@ -115,89 +157,127 @@ impl<'a> Snake<'a> {
         self.tail.push_front(pos);
     }
 
-    pub fn _get_size(&self) -> usize {
+    pub fn get_size(&self) -> usize {
         self.size
     }
 
+    /// Used to get the kind of snake this is, which tells the first player apart from the others
     pub fn get_items(&self) -> Items {
-        self.snake_self.clone()
+        if self.player == 0 {
+            Items::SNAKE
+        } else {
+            Items::OSNAKE
+        }
+    }
+
+    pub fn get_player(&self) -> usize {
+        self.player
     }
 
-    pub fn get_span(&self) -> Span {
-        self.snake.clone()
+    /// Used to set the place of the snake among the players
+    pub(crate) fn set_player(&mut self, player: usize) {
+        self.player = player;
+    }
+
+    pub fn get_team(&self) -> usize {
+        self.team
+    }
+
+    /// The number of fruits the snake has eaten, leaving out food
+    pub fn eaten(&self) -> usize {
+        self.eaten
+    }
+
+    /// Used to get how many steps the snake takes this tick, from the terrain under its head
+    ///
+    /// In mud it only moves every other tick, and on a boost lane it moves twice
+    pub(crate) fn steps(&mut self, board: &Board) -> usize {
+        self.sliding = self.sliding.saturating_sub(1);
+        match board.terrain(&self.pos) {
+            Terrain::Mud => {
+                self.stuck = !self.stuck;
+                usize::from(!self.stuck)
+            }
+            Terrain::Boost => 2,
+            _ => 1,
+        }
     }
 
     fn new_pos(&self, board: &Board) -> Position {
-        let pos = match self.dirr {
-            Directions::UP => Position::new(self.pos.x, self.pos.y - 1),
-            Directions::DOWN => Position::new(self.pos.x, self.pos.y + 1),
-            Directions::LEFT => Position::new(self.pos.x - 1, self.pos.y),
-            Directions::RIGHT => Position::new(self.pos.x + 1, self.pos.y),
-        };
-        board.get_overflow_pos(pos)
+        board.next_pos(&self.pos, &self.dirr)
     }
 
-    pub fn move_snake(
+    /// Moves the snake at index me one step, and puts its new head in the occupancy
+    ///
+    /// Walls, the fruit of other teams, going against an arrow and snakes are lethal,
+    /// except teammates without friendly fire. Food left by dead snakes is eaten by any snake
+    pub(crate) fn move_snake(
         &mut self,
-        board: &mut Board,
+        me: usize,
+        board: &Board,
+        occupancy: &mut Occupancy,
         fruits: &mut Vec<(Position, Items)>,
     ) -> Happen<bool> {
         let pos = self.new_pos(board);
-        return if !board.check_position(&pos, &Items::EMPTY) {
-            for fruit_pos in 0..fruits.len() {
-                let each = &fruits[fruit_pos];
-                if &pos == &each.0 && &self.fruit == &each.1 {
-                    fruits.remove(fruit_pos);
-                    self.set_pos(pos);
-                    return Happen::Some(self.eat());
-                }
-            }
-            Happen::Break
-        } else {
-            self.set_pos(pos);
-            Happen::None
-        };
+        if !occupancy.is_empty(&pos)
+            && (self.friendly_fire || occupancy.contains(&pos, me) || !occupancy.only_team(&pos, self.team))
+        {
+            return Happen::Break;
+        }
+        if !board.terrain(&pos).allows(&self.dirr) {
+            return Happen::Break;
+        }
+        if *board.terrain(&pos) == Terrain::Ice {
+            self.sliding = ICE_TICKS;
+        }
+        // The board is looked at first, so the fruits are only searched when one is eaten
+        let eaten = (board.check_position(&pos, &self.fruit) || board.check_position(&pos, &Items::FOOD))
+            .then(|| fruits.iter().position(|(fruit_pos, _)| fruit_pos == &pos))
+            .flatten();
+        if let Some(index) = eaten {
+            let (_, kind) = fruits.swap_remove(index);
+            self.set_pos(pos.clone());
+            occupancy.add(&pos, me);
+            return Happen::Some(if kind == Items::FOOD { self.grow() } else { self.eat() });
+        }
+        if !board.check_position(&pos, &Items::EMPTY) {
+            return Happen::Break;
+        }
+        self.set_pos(pos.clone());
+        occupancy.add(&pos, me);
+        Happen::None
     }
 
     pub fn get_tail(&self) -> &VecDeque<Position> {
         &self.tail
     }
-
-    pub fn get_info(&self) -> Vec<String> {
-        vec![
-            format!("Current size: {}", self._get_size()),
-            format!("Fruits eaten: {}", self._get_size() - 4),
-        ]
-    }
 }
 
 /// Method used to get the opposite direction of a given direction
-fn opposite(dirr: &Directions) -> Directions {
-    return match dirr {
+pub fn opposite(dirr: &Directions) -> Directions {
+    match dirr {
         Directions::LEFT => Directions::RIGHT,
         Directions::RIGHT => Directions::LEFT,
         Directions::UP => Directions::DOWN,
         Directions::DOWN => Directions::UP,
-    };
+    }
 }
 
 #[cfg(test)]
 mod test_snake {
     use crate::snake::*;
 
-    fn make_snake<'a>() -> Snake<'a> {
-        Snake::new(Position::new(4, 4), Items::SNAKE, Items::FRUIT, test_function)
-    }
-
-    fn test_function(event: Event) -> MoveOpt<Directions> {
-        return MoveOpt::None
+    fn make_snake() -> Snake {
+        Snake::new(Position::new(4, 4), &Team::new(0, true).unwrap())
     }
 
     #[test]
     fn test_get_position() {
         let mut snake = make_snake();
         assert_eq!(Position::new(4, 4), snake.get_pos());
-        let new_pos = snake.move_snake(&mut Board::new(4, 4, true), &mut vec![(Position::new(0, 0), Items::FRUIT)]);
+        let board = Board::new(4, 4, true);
+        let mut occupancy = Occupancy::new(board.get_max_size(), &[]);
+        let new_pos = snake.move_snake(0, &board, &mut occupancy, &mut vec![(Position::new(0, 0), Items::FRUIT)]);
         assert_eq!(Happen::None, new_pos);
     }
 
@@ -212,4 +292,44 @@ mod test_snake {
         assert!(snake.eat());
         assert_eq!(7, snake.size);
     }
+
+    #[test]
+    fn test_food() {
+        let mut snake = make_snake();
+        let mut board = Board::new(8, 8, false);
+        board[Position::new(3, 4)] = Items::FOOD;
+        let mut occupancy = Occupancy::new(board.get_max_size(), &[]);
+        let mut fruits = vec![(Position::new(3, 4), Items::FOOD)];
+        assert_eq!(Happen::Some(true), snake.move_snake(0, &board, &mut occupancy, &mut fruits));
+        // Longer, but without a point
+        assert_eq!(5, snake.get_size());
+        assert_eq!(0, snake.eaten());
+        assert!(fruits.is_empty());
+    }
+
+    #[test]
+    fn test_friendly_fire() {
+        let board = Board::new(8, 8, false);
+        for friendly_fire in [true, false] {
+            let team = Team::new(0, friendly_fire).unwrap();
+            let mut players = vec![Snake::new(Position::new(3, 4), &team), Snake::new(Position::new(4, 4), &team)];
+            let mut occupancy = Occupancy::new(board.get_max_size(), &players);
+            let moved = players[1].move_snake(1, &board, &mut occupancy, &mut vec![]);
+            if friendly_fire {
+                assert_eq!(Happen::Break, moved);
+            } else {
+                assert_eq!(Happen::None, moved);
+                assert_eq!(Position::new(3, 4), players[1].get_pos());
+                assert_eq!(vec![0, 1], occupancy.players(&Position::new(3, 4)).collect::<Vec<_>>());
+            }
+        }
+
+        // Other teams are always lethal
+        let mut players = vec![
+            Snake::new(Position::new(3, 4), &Team::new(1, false).unwrap()),
+            Snake::new(Position::new(4, 4), &Team::new(0, false).unwrap()),
+        ];
+        let mut occupancy = Occupancy::new(board.get_max_size(), &players);
+        assert_eq!(Happen::Break, players[1].move_snake(1, &board, &mut occupancy, &mut vec![]));
+    }
 }
@@ -0,0 +1,118 @@
+use std::collections::VecDeque;
+
+use serde::Serialize;
+
+use crate::{
+    controller::helper_enums::Directions,
+    game::Game,
+    snake::Position,
+    terrain::Terrain,
+    Items,
+};
+
+/// A snapshot of a [`Game`], used by external tools
+#[derive(Serialize, Debug)]
+pub struct GameState {
+    pub tick: usize,
+    pub elapsed_ms: u128,
+    pub width: usize,
+    pub height: usize,
+    pub cells: Vec<Vec<Items>>,
+    pub snakes: Vec<SnakeState>,
+    pub fruits: Vec<FruitState>,
+    pub teams: Vec<TeamState>,
+    /// The cells that are about to become walls
+    pub warnings: Vec<Position>,
+    /// The cells with terrain other than plain ground
+    pub terrain: Vec<TerrainState>,
+}
+
+/// A snapshot of a single snake
+#[derive(Serialize, Debug)]
+pub struct SnakeState {
+    pub team: usize,
+    pub head: Position,
+    pub direction: Directions,
+    pub size: usize,
+    pub tail: VecDeque<Position>,
+    pub alive: bool,
+}
+
+/// A snapshot of a fruit on the board
+#[derive(Serialize, Debug)]
+pub struct FruitState {
+    pub position: Position,
+    pub kind: Items,
+    /// The number of ticks before the fruit goes away, if it does
+    pub expires_in: Option<usize>,
+}
+
+/// A cell with terrain on it
+#[derive(Serialize, Debug)]
+pub struct TerrainState {
+    pub position: Position,
+    pub kind: Terrain,
+}
+
+/// A snapshot of the score of a team
+#[derive(Serialize, Debug)]
+pub struct TeamState {
+    pub id: usize,
+    pub score: usize,
+}
+
+impl GameState {
+    pub fn new(game: &Game) -> Self {
+        let (width, height) = game.board().get_max_size();
+        GameState {
+            tick: game.ticks(),
+            elapsed_ms: game.elapsed().as_millis(),
+            width,
+            height,
+            cells: game.cells(),
+            snakes: game
+                .players()
+                .iter()
+                .map(|player| SnakeState {
+                    team: player.get_team(),
+                    head: player.get_pos(),
+                    direction: player.get_dirr(),
+                    size: player.get_size(),
+                    tail: player.get_tail().clone(),
+                    alive: player.is_alive(),
+                })
+                .collect(),
+            fruits: game
+                .fruits()
+                .iter()
+                .map(|(position, kind)| FruitState {
+                    position: position.clone(),
+                    kind: kind.clone(),
+                    expires_in: game.countdown(position),
+                })
+                .collect(),
+            teams: game
+                .teams()
+                .iter()
+                .map(|team| TeamState {
+                    id: team.get_id(),
+                    score: game.team_score(team),
+                })
+                .collect(),
+            warnings: game.warnings(),
+            terrain: game
+                .board()
+                .terrain_cells()
+                .map(|(position, kind)| TerrainState {
+                    position,
+                    kind: kind.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Serializes the snapshot as a single line of JSON
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+}
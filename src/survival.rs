@@ -0,0 +1,182 @@
+use std::collections::BTreeMap;
+
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{
+    board::Board,
+    controller::helper_enums::Directions,
+    level::SPAWN_ROOM,
+    occupancy::Occupancy,
+    placement::FreeCells,
+    snake::{Position, Snake},
+    Items,
+};
+
+/// The number of times a dead snake comes back, when no number is given
+pub const DEFAULT_LIVES: usize = 3;
+
+/// The game going on until one snake is left, where the bodies of dead snakes turn into food
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Survival {
+    /// The number of ticks before a dead snake comes back, or None if it watches the rest of the game
+    respawn: Option<usize>,
+    /// The number of times every snake comes back, before it watches the rest of the game
+    lives: usize,
+    /// The number of times every snake has died, by index
+    deaths: BTreeMap<usize, usize>,
+    /// The tick every dead snake comes back, by index
+    due: BTreeMap<usize, usize>,
+}
+
+impl Survival {
+    /// Used to parse what happens to dead snakes, either spectate, or respawn:N with lives:N after a comma
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let (rule, lives) = match text.split_once(',') {
+            Some((rule, lives)) => (rule, Some(lives)),
+            None => (text, None),
+        };
+        let respawn = match rule.split_once(':') {
+            None if rule == "spectate" => None,
+            Some(("respawn", ticks)) => Some(
+                ticks
+                    .parse()
+                    .map_err(|_| format!("invalid number {:?} for respawn", ticks))?,
+            ),
+            _ => return Err(format!("unknown rule {:?}, expected spectate or respawn:N", rule)),
+        };
+        let lives = match lives.map(|lives| lives.split_once(':')) {
+            None => DEFAULT_LIVES,
+            Some(_) if respawn.is_none() => return Err("lives only go with respawn:N".to_string()),
+            Some(Some(("lives", count))) => count
+                .parse()
+                .map_err(|_| format!("invalid number {:?} for lives", count))?,
+            Some(_) => return Err(format!("unknown rule in {:?}, expected lives:N after respawn:N", text)),
+        };
+        Ok(Survival {
+            respawn,
+            lives,
+            ..Default::default()
+        })
+    }
+
+    /// Used to check if the game is over, when a single snake is left and no dead snake is coming back,
+    /// or none in a game for one
+    pub fn is_over(&self, players: &[Snake]) -> bool {
+        let alive = players.iter().filter(|player| player.is_alive()).count();
+        alive + self.due.len() < players.len().min(2)
+    }
+
+    /// Used to start the time before the snake at index comes back, if it does and has lives left
+    pub(crate) fn died(&mut self, index: usize, tick: usize) {
+        let deaths = self.deaths.entry(index).or_default();
+        *deaths += 1;
+        if let Some(respawn) = self.respawn.filter(|_| *deaths <= self.lives) {
+            self.due.insert(index, tick + respawn);
+        }
+    }
+
+    /// Used to get the number of times the snake at index can still come back, if dead snakes do
+    pub fn lives_left(&self, index: usize) -> Option<usize> {
+        let deaths = self.deaths.get(&index).copied().unwrap_or(0);
+        self.respawn.map(|_| self.lives.saturating_sub(deaths))
+    }
+
+    /// Used to get the number of ticks before the snake at index comes back, if it does
+    pub fn respawn_in(&self, index: usize, tick: usize) -> Option<usize> {
+        self.due.get(&index).map(|due| due.saturating_sub(tick))
+    }
+
+    /// Used to bring back the snakes whose time has come, on free cells with room in front of them
+    pub(crate) fn update(
+        &mut self,
+        tick: usize,
+        board: &Board,
+        occupancy: &mut Occupancy,
+        free: &mut FreeCells,
+        players: &mut [Snake],
+        rng: &mut impl Rng,
+    ) {
+        let ready: Vec<usize> = self
+            .due
+            .iter()
+            .filter(|(_, due)| **due <= tick)
+            .map(|(index, _)| *index)
+            .collect();
+        for index in ready {
+            let heads: Vec<Position> = players.iter().map(|player| player.get_pos()).collect();
+            let open = |pos: &Position| board.check_position(pos, &Items::EMPTY) && occupancy.is_empty(pos);
+            let cells: Vec<&Position> = free
+                .reachable(board, &heads)
+                .filter(|pos| {
+                    let mut ahead = (*pos).clone();
+                    (0..SPAWN_ROOM).all(|_| {
+                        ahead = board.next_pos(&ahead, &Directions::LEFT);
+                        open(&ahead)
+                    })
+                })
+                .collect();
+            // Tried again next tick, when there is no room
+            if let Some(pos) = cells.choose(rng).map(|pos| (*pos).clone()) {
+                self.due.remove(&index);
+                players[index].respawn(pos.clone());
+                occupancy.add(&pos, index);
+                free.update(&pos, board, occupancy);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod survival_test {
+    use crate::{survival::*, team::Team};
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(None, Survival::parse("spectate").unwrap().respawn);
+        assert_eq!(Some(20), Survival::parse("respawn:20").unwrap().respawn);
+        assert_eq!(DEFAULT_LIVES, Survival::parse("respawn:20").unwrap().lives);
+        assert_eq!(5, Survival::parse("respawn:20,lives:5").unwrap().lives);
+        assert!(Survival::parse("spectate,lives:5").is_err());
+        assert!(Survival::parse("respawn:20,lives").is_err());
+        assert!(Survival::parse("respawn:20,hearts:5").is_err());
+        assert!(Survival::parse("respawn").is_err());
+        assert!(Survival::parse("respawn:soon").is_err());
+        assert!(Survival::parse("ghost").is_err());
+    }
+
+    #[test]
+    fn test_is_over() {
+        let team = Team::new(0, true).unwrap();
+        let mut players = vec![Snake::new(Position::new(3, 3), &team), Snake::new(Position::new(3, 6), &team)];
+        let spectate = Survival::parse("spectate").unwrap();
+        let mut respawn = Survival::parse("respawn:5").unwrap();
+        assert!(!spectate.is_over(&players));
+        players[0].kill();
+        respawn.died(0, 0);
+        assert!(spectate.is_over(&players));
+        assert!(!respawn.is_over(&players));
+        assert!(!spectate.is_over(&players[1..]));
+        assert!(spectate.is_over(&players[..1]));
+    }
+
+    #[test]
+    fn test_lives() {
+        let team = Team::new(0, true).unwrap();
+        let mut players = vec![Snake::new(Position::new(3, 3), &team), Snake::new(Position::new(3, 6), &team)];
+        let mut respawn = Survival::parse("respawn:5,lives:1").unwrap();
+        assert_eq!(Some(1), respawn.lives_left(0));
+        players[0].kill();
+        respawn.died(0, 10);
+        assert_eq!(Some(5), respawn.respawn_in(0, 10));
+        assert!(!respawn.is_over(&players));
+
+        // Back once, and out of lives the next time it dies
+        respawn.due.remove(&0);
+        players[0].respawn(Position::new(3, 3));
+        players[0].kill();
+        respawn.died(0, 20);
+        assert_eq!(Some(0), respawn.lives_left(0));
+        assert_eq!(None, respawn.respawn_in(0, 20));
+        assert!(respawn.is_over(&players));
+    }
+}
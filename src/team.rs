@@ -0,0 +1,69 @@
+use crate::Items;
+
+/// The most teams there can be, one for each kind of snake and fruit
+pub const MAX_TEAMS: usize = 2;
+
+/// The kind of fruit of every team, in order of id
+const FRUITS: [Items; MAX_TEAMS] = [Items::FRUIT, Items::OFRUIT];
+
+/// A team of snakes, sharing a fruit type and a score
+#[derive(Clone, Debug)]
+pub struct Team {
+    id: usize,
+    fruit: Items,
+    friendly_fire: bool,
+}
+
+impl Team {
+    /// Creates a new [`Team`]
+    ///
+    /// id decides the kind of fruit used by the team, and has to be less than MAX_TEAMS
+    /// friendly_fire decides if the bodies of teammates are lethal
+    pub fn new(id: usize, friendly_fire: bool) -> Result<Self, String> {
+        let fruit = FRUITS.get(id).ok_or(format!("there can only be {} teams", MAX_TEAMS))?;
+        Ok(Team {
+            id,
+            fruit: fruit.clone(),
+            friendly_fire,
+        })
+    }
+
+    /// Creates every team there can be, in order of id
+    pub fn all(friendly_fire: bool) -> Vec<Self> {
+        FRUITS
+            .iter()
+            .enumerate()
+            .map(|(id, fruit)| Team {
+                id,
+                fruit: fruit.clone(),
+                friendly_fire,
+            })
+            .collect()
+    }
+
+    pub fn get_id(&self) -> usize {
+        self.id
+    }
+
+    pub fn fruit(&self) -> &Items {
+        &self.fruit
+    }
+
+    pub fn friendly_fire(&self) -> bool {
+        self.friendly_fire
+    }
+}
+
+#[cfg(test)]
+mod team_test {
+    use crate::team::*;
+
+    #[test]
+    fn test_new() {
+        assert_eq!(Items::OFRUIT, *Team::new(1, true).unwrap().fruit());
+        assert!(Team::new(MAX_TEAMS, true).is_err());
+        let teams = Team::all(false);
+        assert_eq!(MAX_TEAMS, teams.len());
+        assert!(teams.iter().enumerate().all(|(id, team)| team.get_id() == id && !team.friendly_fire()));
+    }
+}
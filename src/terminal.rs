@@ -1,17 +1,37 @@
 use crossterm::{
     self, execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    terminal::{self as crossterm_terminal, disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 
 use tui::{
     backend::CrosstermBackend,
-    layout::Rect,
-    text::Spans,
-    widgets::{Block, BorderType, Borders, List, ListItem, Paragraph},
+    layout::{Alignment, Rect},
+    text::{Span, Spans},
+    widgets::{Block, BorderType, Borders, List, ListItem, Paragraph, Wrap},
     Frame, Terminal,
 };
 
-use std::io::{stdout, Stdout};
+use snake::Game;
+
+use crate::{
+    hud::Section,
+    render::{minimap, BoardView, View},
+    theme::Theme,
+};
+
+use std::{
+    io::{self, stdout, Stdout},
+    panic,
+    sync::Once,
+};
+
+/// The columns the numbers in the stats can grow by during a game, from one digit to as many as
+/// the cells of the largest board, which is kept free when the board is sized to the terminal
+const STATS_GROWTH: u16 = 6;
+/// The smallest board that is made when sizing the board to the terminal
+const MIN_AUTO_SIZE: usize = 8;
+/// The smallest camera, in cells, before the terminal is too small
+const MIN_CAMERA: (u16, u16) = (5, 5);
 
 pub struct Term {
     stdout: Stdout,
@@ -20,59 +40,208 @@ pub struct Term {
 }
 
 impl Term {
-    pub fn new(board_size: (usize, usize)) -> Self {
+    /// Creates a new [`Term`], which takes over the terminal until it is dropped, or the game panics
+    pub fn new(board_size: (usize, usize)) -> io::Result<Self> {
         let backend = CrosstermBackend::new(stdout());
-        let board_width = (board_size.0 * 2 + 3) as u16;
-        let board_height = (board_size.1 + 2) as u16;
-        let board_size = (board_width, board_height);
+        let board_size = board_rect_size(board_size);
         let term = Term {
             stdout: stdout(),
-            terminal: Terminal::new(backend).unwrap(),
+            terminal: Terminal::new(backend)?,
             board_size,
         };
-        enable_raw_mode().unwrap();
-        execute!(&term.stdout, EnterAlternateScreen).unwrap();
-        term
+        restore_on_panic();
+        enable_raw_mode()?;
+        execute!(&term.stdout, EnterAlternateScreen)?;
+        Ok(term)
     }
 
-    pub fn render<'a>(
-        &mut self,
-        board: Vec<Spans<'a>>,
-        stats: &Vec<String>,
-    ) {
+    /// Used to draw the game and the stats
+    ///
+    /// When the board does not fit, every player gets a camera following them, and a minimap is drawn below the stats
+    pub fn render(&mut self, game: &Game, theme: &Theme, stats: &[Section]) -> io::Result<()> {
+        let board_size = self.board_size;
+        let cells = game.board().get_max_size();
+        let players = game.players().len();
+        self.terminal
+            .draw(|f| match layout(f.size(), board_size, stats_size(stats), players) {
+                Layout::Beside(board_rect, stats_rect) | Layout::Below(board_rect, stats_rect) => {
+                    print_game(f, BoardView::new(game, theme, View::whole(cells)), "Snake", board_rect);
+                    print_stats(f, stats, stats_rect);
+                }
+                Layout::Cameras(cameras, stats_rect) => {
+                    for (index, (camera, player)) in cameras.iter().zip(game.players()).enumerate() {
+                        let size = ((camera.width as usize - 2) / 2, camera.height as usize - 2);
+                        let view = View::camera(&player.get_pos(), cells, size);
+                        let title = format!("Player {}", index + 1);
+                        print_game(f, BoardView::new(game, theme, view), &title, *camera);
+                    }
+                    let stats_height = stats_size(stats).1.min(stats_rect.height);
+                    print_stats(f, stats, Rect { height: stats_height, ..stats_rect });
+                    let map_rect = Rect {
+                        y: stats_rect.y + stats_height,
+                        height: stats_rect.height - stats_height,
+                        ..stats_rect
+                    };
+                    if map_rect.height > 2 {
+                        let size = (map_rect.width as usize - 2, map_rect.height as usize - 2);
+                        print_minimap(f, minimap(game, theme, size), map_rect);
+                    }
+                }
+                Layout::TooSmall => print_too_small(f, board_size),
+            })
+            .map(|_| ())
+    }
+
+    /// Used to draw a message over the whole terminal, like the victory screen
+    pub fn message(&mut self, title: &str, lines: &[String]) -> io::Result<()> {
         self.terminal
             .draw(|f| {
-                let board_rect = Rect {
-                    x: 0,
-                    y: 0,
-                    width: self.board_size.0,
-                    height: self.board_size.1,
-                };
-                let stats_rect = Rect {
-                    x: self.board_size.0 + 1,
-                    y: 0,
-                    width: self.board_size.0,
-                    height: self.board_size.1,
-                };
-                print_board(board, f, board_rect);
-                print_stats(stats, f, stats_rect);
+                let area = f.size();
+                let width = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0) + 4;
+                let (width, height) = ((width as u16).min(area.width), (lines.len() as u16 + 2).min(area.height));
+                let chunk = Rect::new(
+                    area.x + (area.width - width) / 2,
+                    area.y + (area.height - height) / 2,
+                    width,
+                    height,
+                );
+                let text: Vec<Spans> = lines.iter().map(|line| Spans::from(line.as_str())).collect();
+                f.render_widget(Paragraph::new(text).alignment(Alignment::Center).block(border(title)), chunk);
             })
-            .unwrap();
+            .map(|_| ())
+    }
+
+    /// Used to redraw everything after the terminal changed size
+    pub fn resize(&mut self) -> io::Result<()> {
+        self.terminal.autoresize()?;
+        self.terminal.clear()
+    }
+}
+
+/// Used to get the size of the board on the screen, where every cell is two columns wide
+fn board_rect_size(board_size: (usize, usize)) -> (u16, u16) {
+    ((board_size.0 * 2 + 3) as u16, (board_size.1 + 2) as u16)
+}
+
+/// Used to get the largest board that fits in the terminal, next to the stats
+///
+/// stats are the stats at the start of the game, which are as wide as the panel gets, but for the numbers
+pub fn auto_size(stats: &[Section]) -> io::Result<(usize, usize)> {
+    Ok(fit_board(crossterm_terminal::size()?, stats_size(stats).0 + STATS_GROWTH))
+}
+
+/// Used to get the largest board that fits in a terminal of the given size, next to stats of the given width
+fn fit_board(terminal: (u16, u16), stats_width: u16) -> (usize, usize) {
+    let width = terminal.0.saturating_sub(stats_width + 1 + 3) / 2;
+    let height = terminal.1.saturating_sub(2);
+    ((width as usize).max(MIN_AUTO_SIZE), (height as usize).max(MIN_AUTO_SIZE))
+}
+
+/// Where the board and the stats are placed in the terminal
+#[derive(Debug, PartialEq)]
+enum Layout {
+    /// The stats are to the right of the board
+    Beside(Rect, Rect),
+    /// The terminal is too narrow, so the stats are below the board
+    Below(Rect, Rect),
+    /// The board does not fit, so there is a camera for every player, and the stats are to the right
+    Cameras(Vec<Rect>, Rect),
+    /// The board does not fit at all
+    TooSmall,
+}
+
+/// Used to split the area left of the stats into a camera for every player
+fn cameras(area: Rect, stats_width: u16, players: usize) -> Layout {
+    let players = players.max(1) as u16;
+    let width = area.width.saturating_sub(stats_width + 1);
+    let (min_width, min_height) = (MIN_CAMERA.0 * 2 + 2, MIN_CAMERA.1 + 2);
+    // Cells are two columns wide, so the cameras are side by side when there is more room that way
+    let beside = width / 2 >= area.height;
+    let (camera_width, camera_height) = if beside {
+        (width / players, area.height)
+    } else {
+        (width, area.height / players)
+    };
+    if camera_width < min_width || camera_height < min_height {
+        return Layout::TooSmall;
+    }
+    let cameras = (0..players)
+        .map(|index| {
+            if beside {
+                Rect::new(area.x + index * camera_width, area.y, camera_width, camera_height)
+            } else {
+                Rect::new(area.x, area.y + index * camera_height, camera_width, camera_height)
+            }
+        })
+        .collect();
+    Layout::Cameras(cameras, Rect::new(area.x + width + 1, area.y, stats_width, area.height))
+}
+
+/// Used to get the size of the stats panel, with its borders
+fn stats_size(stats: &[Section]) -> (u16, u16) {
+    let width = stats
+        .iter()
+        .flat_map(|section| {
+            let lines = section.lines.iter().map(|line| line.chars().count() + 2);
+            lines.chain([section.title.chars().count()])
+        })
+        .max()
+        .unwrap_or(0)
+        + 4;
+    let height: usize = stats.iter().map(|section| section.lines.len() + 1).sum();
+    (width as u16, height as u16 + 2)
+}
+
+/// Used to place the board and the stats in the area of the terminal
+fn layout(area: Rect, board: (u16, u16), stats: (u16, u16), players: usize) -> Layout {
+    if area.width < board.0 || area.height < board.1 {
+        return cameras(area, stats.0, players);
+    }
+    let board_rect = Rect::new(area.x, area.y, board.0, board.1);
+    if area.width >= board.0 + 1 + stats.0 {
+        let stats_rect = Rect::new(area.x + board.0 + 1, area.y, stats.0, board.1.max(stats.1).min(area.height));
+        Layout::Beside(board_rect, stats_rect)
+    } else {
+        let height = (area.height - board.1).min(stats.1);
+        Layout::Below(board_rect, Rect::new(area.x, area.y + board.1, stats.0.min(area.width), height))
     }
 }
 
 impl Drop for Term {
     fn drop(&mut self) {
-        disable_raw_mode().unwrap();
-        execute!(self.terminal.backend_mut(), LeaveAlternateScreen).unwrap();
+        restore();
     }
 }
 
+/// Used to give the terminal back, as it was before the game
+///
+/// Errors are ignored, since this is done while leaving anyway
+fn restore() {
+    let _ = disable_raw_mode();
+    let _ = execute!(stdout(), LeaveAlternateScreen);
+}
+
+/// Used to give the terminal back before a panic is printed, so the message can be read
+fn restore_on_panic() {
+    static HOOK: Once = Once::new();
+    HOOK.call_once(|| {
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            restore();
+            hook(info);
+        }));
+    });
+}
+
 /// Used to print the stats to the screen
-fn print_stats<B: tui::backend::Backend>(stats: &Vec<String>, f: &mut Frame<B>, chunk: Rect) {
+fn print_stats<B: tui::backend::Backend>(f: &mut Frame<B>, stats: &[Section], chunk: Rect) {
     let rows: Vec<ListItem> = stats
         .iter()
-        .map(|x| ListItem::new(format!("{x}")))
+        .flat_map(|section| {
+            let title = ListItem::new(Spans::from(Span::styled(section.title.clone(), section.style)));
+            let lines = section.lines.iter().map(|line| ListItem::new(format!("  {}", line)));
+            [title].into_iter().chain(lines)
+        })
         .collect();
     let text = List::new(rows).block(
         Block::default()
@@ -80,24 +249,122 @@ fn print_stats<B: tui::backend::Backend>(stats: &Vec<String>, f: &mut Frame<B>,
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded),
     );
-    let chunk = Rect::new(chunk.x, chunk.y, (stats[0].len() + 4) as u16, chunk.height);
     f.render_widget(text, chunk);
 }
 
+/// Used to tell that the terminal is too small for the board
+fn print_too_small<B: tui::backend::Backend>(f: &mut Frame<B>, board: (u16, u16)) {
+    let area = f.size();
+    let text = Paragraph::new(format!(
+        "Terminal too small: the board needs {}x{}, but there is only {}x{}",
+        board.0, board.1, area.width, area.height
+    ))
+    .wrap(Wrap { trim: true });
+    f.render_widget(text, area);
+}
+
+/// Used to get the border drawn around the board, the cameras and the minimap
+fn border(title: &str) -> Block<'_> {
+    Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+}
+
 ///used to print the board to the screen
 ///
-///board is the board to print
-///stdout is used to print
-fn print_board<'a, B: tui::backend::Backend>(
-    paragraph: Vec<Spans<'a>>,
-    f: &mut Frame<B>,
-    chunk: Rect,
-) {
-    let text = Paragraph::new(paragraph.clone()).block(
-        Block::default()
-            .title("Snake")
-            .borders(Borders::ALL)
-            .border_type(BorderType::Rounded),
-    );
-    f.render_widget(text, chunk)
+///board is the part of the board to print
+///title is written on the border
+fn print_game<B: tui::backend::Backend>(f: &mut Frame<B>, board: BoardView, title: &str, chunk: Rect) {
+    let block = border(title);
+    let inner = block.inner(chunk);
+    f.render_widget(block, chunk);
+    f.render_widget(board, inner);
+}
+
+///used to print the minimap to the screen
+fn print_minimap<B: tui::backend::Backend>(f: &mut Frame<B>, map: Vec<Spans>, chunk: Rect) {
+    f.render_widget(Paragraph::new(map).block(border("map")), chunk)
+}
+
+#[cfg(test)]
+mod terminal_test {
+    use crate::terminal::*;
+
+    #[test]
+    fn test_layout() {
+        let board = (35, 18);
+        let stats = (20, 6);
+        assert_eq!(
+            Layout::Beside(Rect::new(0, 0, 35, 18), Rect::new(36, 0, 20, 18)),
+            layout(Rect::new(0, 0, 80, 24), board, stats, 1)
+        );
+        assert_eq!(
+            Layout::Below(Rect::new(0, 0, 35, 18), Rect::new(0, 18, 20, 6)),
+            layout(Rect::new(0, 0, 40, 30), board, stats, 1)
+        );
+        assert_eq!(Layout::TooSmall, layout(Rect::new(0, 0, 30, 30), board, stats, 1));
+    }
+
+    #[test]
+    fn test_cameras() {
+        let board = (1003, 502);
+        let stats = (20, 6);
+        assert_eq!(
+            Layout::Cameras(vec![Rect::new(0, 0, 59, 24)], Rect::new(60, 0, 20, 24)),
+            layout(Rect::new(0, 0, 80, 24), board, stats, 1)
+        );
+        assert_eq!(
+            Layout::Cameras(
+                vec![Rect::new(0, 0, 29, 12), Rect::new(29, 0, 29, 12)],
+                Rect::new(60, 0, 20, 12)
+            ),
+            layout(Rect::new(0, 0, 80, 12), board, stats, 2)
+        );
+        assert_eq!(
+            Layout::Cameras(
+                vec![Rect::new(0, 0, 59, 20), Rect::new(0, 20, 59, 20)],
+                Rect::new(60, 0, 20, 40)
+            ),
+            layout(Rect::new(0, 0, 80, 40), board, stats, 2)
+        );
+        assert_eq!(Layout::TooSmall, layout(Rect::new(0, 0, 30, 6), board, stats, 1));
+    }
+
+    #[test]
+    fn test_fit_board() {
+        let (x, y) = fit_board((80, 24), 24);
+        assert_eq!((26, 22), (x, y));
+        let (width, height) = board_rect_size((x, y));
+        assert!(width + 1 + 24 <= 80);
+        assert!(height <= 24);
+        assert_eq!((MIN_AUTO_SIZE, MIN_AUTO_SIZE), fit_board((10, 5), 24));
+    }
+
+    #[test]
+    fn test_stats_width() {
+        use crate::hud::{hud, preview};
+        use snake::{scores::Record, Game, Level, Snake, Team};
+
+        let teams = vec![Team::new(0, true).unwrap(), Team::new(1, true).unwrap()];
+        let controls = [Some("arrow keys"), None];
+        let theme = Theme::default();
+        let reserved = stats_size(&preview(&teams, 2, &theme, &controls)).0 + STATS_GROWTH;
+
+        // A long game on the largest board, with every number as long as it gets
+        let level = Level::new("large", 1000, 1000, false);
+        let mut players: Vec<Snake> =
+            (0..2).map(|index| Snake::new(level.spawn_position(index, 2), &teams[index])).collect();
+        for player in players.iter_mut() {
+            for _ in 0..999_990 {
+                player.eat();
+            }
+        }
+        let game = Game::new(level.board(), players, teams);
+        let best = Record {
+            score: 999_999,
+            fastest_ms: Some(5_999_000),
+        };
+        assert!(stats_size(&hud(&game, &theme, Some(&best), &controls)).0 <= reserved);
+    }
 }
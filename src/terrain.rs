@@ -0,0 +1,45 @@
+use serde::Serialize;
+
+use crate::{controller::helper_enums::Directions, snake::opposite};
+
+/// The number of ticks a snake can not turn after it has gone onto ice
+pub const ICE_TICKS: usize = 3;
+
+/// The ground of a cell, which changes how the snakes move over it
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub enum Terrain {
+    #[default]
+    Plain,
+    /// A snake with its head in mud only moves every other tick
+    Mud,
+    /// A snake with its head on a boost lane moves two steps every tick
+    Boost,
+    /// A snake that goes onto ice can not turn for a while
+    Ice,
+    /// A cell that can not be gone onto against the arrow
+    Arrow(Directions),
+}
+
+impl Terrain {
+    /// Used to check if a snake heading in the direction can go onto the cell
+    pub fn allows(&self, dirr: &Directions) -> bool {
+        match self {
+            Terrain::Arrow(arrow) => *arrow != opposite(dirr),
+            _ => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod terrain_test {
+    use crate::terrain::*;
+
+    #[test]
+    fn test_allows() {
+        let arrow = Terrain::Arrow(Directions::UP);
+        assert!(arrow.allows(&Directions::UP));
+        assert!(arrow.allows(&Directions::LEFT));
+        assert!(!arrow.allows(&Directions::DOWN));
+        assert!(Terrain::Mud.allows(&Directions::DOWN));
+    }
+}
@@ -0,0 +1,622 @@
+use serde::Deserialize;
+use snake::{controller::helper_enums::Directions, team::MAX_TEAMS, Items, Terrain, MAX_PLAYERS};
+use tui::style::{Color, Modifier, Style};
+
+use crate::consts::*;
+
+use std::fs;
+
+/// The names of the themes that are always available
+pub const BUILTIN: [&str; 5] = ["classic", "unicode", "emoji", "high-contrast", "monochrome"];
+
+/// The glyphs used to draw the board, where every glyph is two columns wide
+#[derive(Clone, Debug)]
+pub struct Glyphs {
+    pub wall: String,
+    pub empty: String,
+    /// Every team has its own fruit, and every player its own head, so they can be told apart without colours
+    pub fruits: [String; MAX_TEAMS],
+    pub heads: [String; MAX_PLAYERS],
+    pub tail: String,
+    pub horizontal: String,
+    pub vertical: String,
+    /// The corners, named by the two sides they connect
+    pub up_right: String,
+    pub up_left: String,
+    pub down_right: String,
+    pub down_left: String,
+    /// A cell that is about to become a wall
+    pub warning: String,
+    pub portal: String,
+    /// What is left of a dead snake
+    pub food: String,
+    pub mud: String,
+    pub boost: String,
+    pub ice: String,
+    /// The arrows pointing up, down, left and right
+    pub arrows: [String; 4],
+}
+
+impl Glyphs {
+    fn body(glyph: &str) -> [String; 6] {
+        [0; 6].map(|_| glyph.to_string())
+    }
+
+    fn new(
+        wall: &str,
+        empty: &str,
+        fruits: [&str; MAX_TEAMS],
+        heads: [&str; MAX_PLAYERS],
+        tail: &str,
+        body: [String; 6],
+    ) -> Self {
+        let [horizontal, vertical, up_right, up_left, down_right, down_left] = body;
+        Glyphs {
+            wall: wall.to_string(),
+            empty: empty.to_string(),
+            fruits: fruits.map(str::to_string),
+            heads: heads.map(str::to_string),
+            tail: tail.to_string(),
+            horizontal,
+            vertical,
+            up_right,
+            up_left,
+            down_right,
+            down_left,
+            warning: WARNING.to_string(),
+            portal: PORTAL.to_string(),
+            food: FOOD.to_string(),
+            mud: MUD.to_string(),
+            boost: BOOST.to_string(),
+            ice: ICE.to_string(),
+            arrows: ARROWS.map(str::to_string),
+        }
+    }
+
+    /// Used to get the glyph of a part of the body, which connects the two sides
+    fn connecting(&self, a: &Directions, b: &Directions) -> &str {
+        use Directions::*;
+        match (a, b) {
+            (LEFT, RIGHT) | (RIGHT, LEFT) | (LEFT, LEFT) | (RIGHT, RIGHT) => &self.horizontal,
+            (UP, DOWN) | (DOWN, UP) | (UP, UP) | (DOWN, DOWN) => &self.vertical,
+            (UP, RIGHT) | (RIGHT, UP) => &self.up_right,
+            (UP, LEFT) | (LEFT, UP) => &self.up_left,
+            (DOWN, RIGHT) | (RIGHT, DOWN) => &self.down_right,
+            (DOWN, LEFT) | (LEFT, DOWN) => &self.down_left,
+        }
+    }
+}
+
+/// Decides how the cells of the board are drawn, so the game itself knows nothing about it
+#[derive(Clone, Debug)]
+pub struct Theme {
+    glyphs: Glyphs,
+    wall: Style,
+    warning: Style,
+    portal: Style,
+    food: Style,
+    mud: Style,
+    boost: Style,
+    ice: Style,
+    arrow: Style,
+    snakes: [Style; MAX_TEAMS],
+    fruits: [Style; MAX_TEAMS],
+}
+
+/// A part of a snake
+pub enum Segment {
+    Head,
+    /// A part of the body, with the sides it is connected to
+    Body(Directions, Directions),
+    Tail,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::builtin("classic").unwrap()
+    }
+}
+
+impl Theme {
+    /// Used to get one of the built-in themes by name
+    pub fn builtin(name: &str) -> Option<Self> {
+        let theme = match name {
+            "classic" => Theme {
+                glyphs: Glyphs::new(WALL, EMPTY, FRUITS, HEADS, TAIL, Glyphs::body(SNEK)),
+                wall: Style::default().bg(Color::White),
+                warning: Style::default().fg(Color::Black).bg(Color::Yellow),
+                portal: Style::default().fg(Color::Black).bg(Color::Magenta),
+                food: Style::default().fg(Color::LightYellow),
+                mud: Style::default().fg(Color::Black).bg(Color::DarkGray),
+                boost: Style::default().fg(Color::LightGreen),
+                ice: Style::default().fg(Color::Black).bg(Color::Cyan),
+                arrow: Style::default().fg(Color::LightBlue),
+                snakes: [Style::default().bg(Color::Green), Style::default().bg(Color::Yellow)],
+                fruits: [Style::default().fg(Color::Red), Style::default().fg(Color::Blue)],
+            },
+            "unicode" => Theme {
+                glyphs: Glyphs {
+                    warning: "░░".to_string(),
+                    portal: "◎ ".to_string(),
+                    food: "∘ ".to_string(),
+                    mud: "≈≈".to_string(),
+                    boost: "»»".to_string(),
+                    ice: "▫ ".to_string(),
+                    arrows: ["↑ ", "↓ ", "← ", "→ "].map(str::to_string),
+                    ..Glyphs::new(
+                        "██",
+                        "  ",
+                        ["● ", "▲ "],
+                        ["◆ ", "■ "],
+                        "• ",
+                        ["━━", "┃ ", "┗━", "┛ ", "┏━", "┓ "].map(str::to_string),
+                    )
+                },
+                wall: Style::default().fg(Color::Gray),
+                warning: Style::default().fg(Color::Yellow),
+                portal: Style::default().fg(Color::Magenta),
+                food: Style::default().fg(Color::LightYellow),
+                mud: Style::default().fg(Color::DarkGray),
+                boost: Style::default().fg(Color::LightGreen),
+                ice: Style::default().fg(Color::Cyan),
+                arrow: Style::default().fg(Color::LightBlue),
+                snakes: [Style::default().fg(Color::Green), Style::default().fg(Color::Yellow)],
+                fruits: [Style::default().fg(Color::Red), Style::default().fg(Color::Blue)],
+            },
+            "emoji" => Theme {
+                glyphs: Glyphs {
+                    warning: "🚧".to_string(),
+                    portal: "🌀".to_string(),
+                    food: "🍖".to_string(),
+                    mud: "🟫".to_string(),
+                    boost: "⚡".to_string(),
+                    ice: "🧊".to_string(),
+                    arrows: ["⏫", "⏬", "⏪", "⏩"].map(str::to_string),
+                    ..Glyphs::new("🧱", "  ", ["🍎", "🍇"], ["🐍", "🐲"], "🟢", Glyphs::body("🟩"))
+                },
+                wall: Style::default(),
+                warning: Style::default(),
+                portal: Style::default(),
+                food: Style::default(),
+                mud: Style::default(),
+                boost: Style::default(),
+                ice: Style::default(),
+                arrow: Style::default(),
+                snakes: [Style::default(), Style::default().bg(Color::Yellow)],
+                fruits: [Style::default(), Style::default()],
+            },
+            "high-contrast" => {
+                let bold = Style::default().add_modifier(Modifier::BOLD);
+                Theme {
+                    glyphs: Glyphs {
+                        warning: "!!".to_string(),
+                        portal: "{}".to_string(),
+                        food: "oo".to_string(),
+                        mud: "~~".to_string(),
+                        boost: "++".to_string(),
+                        ice: "--".to_string(),
+                        arrows: ["^^", "vv", "<<", ">>"].map(str::to_string),
+                        ..Glyphs::new("██", "  ", ["<>", "[]"], ["@@", "QQ"], "::", Glyphs::body("##"))
+                    },
+                    wall: bold.fg(Color::White),
+                    warning: bold.fg(Color::LightYellow),
+                    portal: bold.fg(Color::LightMagenta),
+                    food: bold.fg(Color::White),
+                    mud: bold.fg(Color::DarkGray),
+                    boost: bold.fg(Color::LightGreen),
+                    ice: bold.fg(Color::LightCyan),
+                    arrow: bold.fg(Color::LightBlue),
+                    snakes: [bold.fg(Color::Black).bg(Color::LightGreen), bold.fg(Color::Black).bg(Color::LightYellow)],
+                    fruits: [bold.fg(Color::LightRed), bold.fg(Color::LightCyan)],
+                }
+            }
+            "monochrome" => Theme {
+                glyphs: Glyphs {
+                    warning: "!!".to_string(),
+                    portal: "{}".to_string(),
+                    food: "oo".to_string(),
+                    mud: "~~".to_string(),
+                    boost: "++".to_string(),
+                    ice: "--".to_string(),
+                    arrows: ["^^", "vv", "<<", ">>"].map(str::to_string),
+                    ..Glyphs::new(
+                        "##",
+                        "  ",
+                        ["()", "[]"],
+                        ["@@", "QQ"],
+                        "..",
+                        ["==", "||", "'=", "='", ",=", "=,"].map(str::to_string),
+                    )
+                },
+                wall: Style::default(),
+                warning: Style::default().add_modifier(Modifier::SLOW_BLINK),
+                portal: Style::default().add_modifier(Modifier::BOLD),
+                food: Style::default(),
+                mud: Style::default(),
+                boost: Style::default().add_modifier(Modifier::BOLD),
+                ice: Style::default(),
+                arrow: Style::default().add_modifier(Modifier::BOLD),
+                snakes: [Style::default(), Style::default().add_modifier(Modifier::REVERSED)],
+                fruits: [Style::default(), Style::default()],
+            },
+            _ => return None,
+        };
+        Some(theme)
+    }
+
+    /// Used to get a built-in theme by name, or load a theme from a file
+    pub fn load(name: &str) -> Result<Self, String> {
+        if let Some(theme) = Self::builtin(name) {
+            return Ok(theme);
+        }
+        let text = fs::read_to_string(name).map_err(|err| {
+            format!(
+                "{:?} is not one of {}, and could not be read as a file: {}",
+                name,
+                BUILTIN.join(", "),
+                err
+            )
+        })?;
+        Self::from_json(&text).map_err(|err| format!("invalid theme file {:?}: {}", name, err))
+    }
+
+    /// Used to read a theme written as JSON
+    fn from_json(text: &str) -> Result<Self, String> {
+        let file: ThemeFile = serde_json::from_str(text).map_err(|err| err.to_string())?;
+        file.into_theme()
+    }
+
+    /// Used to get how a cell of the board is drawn
+    pub fn cell(&self, item: &Items) -> (&str, Style) {
+        match item {
+            Items::WALL => (&self.glyphs.wall, self.wall),
+            Items::EMPTY => (&self.glyphs.empty, Style::default()),
+            Items::SNAKE => self.snake(0, 0, &Segment::Tail),
+            Items::OSNAKE => self.snake(1, 1, &Segment::Tail),
+            Items::FRUIT => self.fruit(0),
+            Items::OFRUIT => self.fruit(1),
+            Items::PORTAL => (&self.glyphs.portal, self.portal),
+            Items::FOOD => (&self.glyphs.food, self.food),
+        }
+    }
+
+    /// Used to get how a part of the snake of a player is drawn, in the colour of its team and with its own head
+    pub fn snake(&self, team: usize, player: usize, segment: &Segment) -> (&str, Style) {
+        let glyph = match segment {
+            Segment::Head => &self.glyphs.heads[player % MAX_PLAYERS],
+            Segment::Body(a, b) => self.glyphs.connecting(a, b),
+            Segment::Tail => &self.glyphs.tail,
+        };
+        (glyph, self.snakes[team % MAX_TEAMS])
+    }
+
+    /// Used to get how the fruit of a team is drawn
+    pub fn fruit(&self, team: usize) -> (&str, Style) {
+        (&self.glyphs.fruits[team], self.fruits[team])
+    }
+
+    /// Used to get how an empty cell with terrain on it is drawn
+    pub fn terrain(&self, terrain: &Terrain) -> (&str, Style) {
+        match terrain {
+            Terrain::Plain => (&self.glyphs.empty, Style::default()),
+            Terrain::Mud => (&self.glyphs.mud, self.mud),
+            Terrain::Boost => (&self.glyphs.boost, self.boost),
+            Terrain::Ice => (&self.glyphs.ice, self.ice),
+            Terrain::Arrow(dirr) => {
+                let index = match dirr {
+                    Directions::UP => 0,
+                    Directions::DOWN => 1,
+                    Directions::LEFT => 2,
+                    Directions::RIGHT => 3,
+                };
+                (&self.glyphs.arrows[index], self.arrow)
+            }
+        }
+    }
+
+    /// Used to get how a cell that is about to become a wall is drawn
+    pub fn warning(&self) -> (&str, Style) {
+        (&self.glyphs.warning, self.warning)
+    }
+}
+
+/// The names of the palettes
+pub const PALETTES: [&str; 5] = ["default", "deuteranopia", "protanopia", "tritanopia", "none"];
+
+/// Colours that replace the ones of a theme, so the game can be played with colour vision deficiencies
+#[derive(Clone, Debug, PartialEq)]
+pub enum Palette {
+    /// The colours of the theme
+    Default,
+    /// Blue and orange, safe for red-green colour blindness
+    RedGreen,
+    /// Vermillion and bluish green, safe for blue-yellow colour blindness
+    BlueYellow,
+    /// No colours at all, for terminals without them
+    None,
+}
+
+impl Palette {
+    /// Used to get a palette by name
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "default" => Ok(Palette::Default),
+            "deuteranopia" | "protanopia" => Ok(Palette::RedGreen),
+            "tritanopia" => Ok(Palette::BlueYellow),
+            "none" => Ok(Palette::None),
+            _ => Err(format!("unknown palette {:?}, expected one of {}", name, PALETTES.join(", "))),
+        }
+    }
+
+    /// Used to get the palette to use, where NO_COLOR turns off all colours
+    ///
+    /// See <https://no-color.org>
+    pub fn or_no_color(self) -> Self {
+        match std::env::var_os("NO_COLOR") {
+            Some(value) if !value.is_empty() => Palette::None,
+            _ => self,
+        }
+    }
+
+    /// Used to get the colours of the snakes and the fruit of every team
+    fn colors(&self) -> Option<([Color; MAX_TEAMS], [Color; MAX_TEAMS])> {
+        // From the palette by Okabe and Ito
+        match self {
+            Palette::RedGreen => Some((
+                [Color::Rgb(0, 114, 178), Color::Rgb(230, 159, 0)],
+                [Color::Rgb(86, 180, 233), Color::Rgb(213, 94, 0)],
+            )),
+            Palette::BlueYellow => Some((
+                [Color::Rgb(213, 94, 0), Color::Rgb(0, 158, 115)],
+                [Color::Rgb(204, 121, 167), Color::Rgb(86, 180, 233)],
+            )),
+            Palette::Default | Palette::None => None,
+        }
+    }
+}
+
+/// Used to change the colour of a style, keeping if it colours the text or the background
+fn recolor(style: Style, color: Color) -> Style {
+    if style.bg.is_some() {
+        style.bg(color)
+    } else {
+        style.fg(color)
+    }
+}
+
+/// Used to take the colours out of a style, keeping bold, reversed and the like
+fn uncolor(style: Style) -> Style {
+    Style {
+        fg: None,
+        bg: None,
+        ..style
+    }
+}
+
+impl Theme {
+    /// Used to change the colours of the theme to those of a palette
+    pub fn with_palette(mut self, palette: &Palette) -> Self {
+        if let Some((snakes, fruits)) = palette.colors() {
+            for (style, color) in self.snakes.iter_mut().zip(snakes) {
+                *style = recolor(*style, color);
+            }
+            for (style, color) in self.fruits.iter_mut().zip(fruits) {
+                *style = recolor(*style, color);
+            }
+        }
+        if *palette == Palette::None {
+            self.wall = uncolor(self.wall);
+            self.warning = uncolor(self.warning);
+            self.portal = uncolor(self.portal);
+            self.food = uncolor(self.food);
+            self.mud = uncolor(self.mud);
+            self.boost = uncolor(self.boost);
+            self.ice = uncolor(self.ice);
+            self.arrow = uncolor(self.arrow);
+            self.snakes = self.snakes.map(uncolor);
+            self.fruits = self.fruits.map(uncolor);
+        }
+        self
+    }
+}
+
+/// A theme as written in a file, where everything left out is taken from the base theme
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct ThemeFile {
+    base: Option<String>,
+    glyphs: GlyphsFile,
+    colors: ColorsFile,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct GlyphsFile {
+    wall: Option<String>,
+    empty: Option<String>,
+    fruits: Option<[String; MAX_TEAMS]>,
+    heads: Option<[String; MAX_PLAYERS]>,
+    tail: Option<String>,
+    horizontal: Option<String>,
+    vertical: Option<String>,
+    up_right: Option<String>,
+    up_left: Option<String>,
+    down_right: Option<String>,
+    down_left: Option<String>,
+    warning: Option<String>,
+    portal: Option<String>,
+    food: Option<String>,
+    mud: Option<String>,
+    boost: Option<String>,
+    ice: Option<String>,
+    arrows: Option<[String; 4]>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct ColorsFile {
+    wall: Option<String>,
+    warning: Option<String>,
+    portal: Option<String>,
+    food: Option<String>,
+    mud: Option<String>,
+    boost: Option<String>,
+    ice: Option<String>,
+    arrow: Option<String>,
+    snakes: Option<[String; MAX_TEAMS]>,
+    fruits: Option<[String; MAX_TEAMS]>,
+}
+
+impl ThemeFile {
+    fn into_theme(self) -> Result<Theme, String> {
+        let base = self.base.unwrap_or_else(|| "classic".to_string());
+        let mut theme = Theme::builtin(&base).ok_or_else(|| format!("unknown base theme {:?}", base))?;
+
+        let glyphs = &mut theme.glyphs;
+        let overrides = [
+            (&mut glyphs.wall, self.glyphs.wall),
+            (&mut glyphs.empty, self.glyphs.empty),
+            (&mut glyphs.tail, self.glyphs.tail),
+            (&mut glyphs.horizontal, self.glyphs.horizontal),
+            (&mut glyphs.vertical, self.glyphs.vertical),
+            (&mut glyphs.up_right, self.glyphs.up_right),
+            (&mut glyphs.up_left, self.glyphs.up_left),
+            (&mut glyphs.down_right, self.glyphs.down_right),
+            (&mut glyphs.down_left, self.glyphs.down_left),
+            (&mut glyphs.warning, self.glyphs.warning),
+            (&mut glyphs.portal, self.glyphs.portal),
+            (&mut glyphs.food, self.glyphs.food),
+            (&mut glyphs.mud, self.glyphs.mud),
+            (&mut glyphs.boost, self.glyphs.boost),
+            (&mut glyphs.ice, self.glyphs.ice),
+        ];
+        for (glyph, value) in overrides {
+            if let Some(value) = value {
+                *glyph = value;
+            }
+        }
+        if let Some(fruits) = self.glyphs.fruits {
+            glyphs.fruits = fruits;
+        }
+        if let Some(heads) = self.glyphs.heads {
+            glyphs.heads = heads;
+        }
+        if let Some(arrows) = self.glyphs.arrows {
+            glyphs.arrows = arrows;
+        }
+
+        // Only the colour changes, so the base theme decides if it is the text or the background
+        let colors = [
+            (&mut theme.wall, self.colors.wall),
+            (&mut theme.warning, self.colors.warning),
+            (&mut theme.portal, self.colors.portal),
+            (&mut theme.food, self.colors.food),
+            (&mut theme.mud, self.colors.mud),
+            (&mut theme.boost, self.colors.boost),
+            (&mut theme.ice, self.colors.ice),
+            (&mut theme.arrow, self.colors.arrow),
+        ];
+        for (style, color) in colors {
+            if let Some(color) = color {
+                *style = recolor(*style, parse_color(&color)?);
+            }
+        }
+        if let Some(snakes) = self.colors.snakes {
+            for (style, color) in theme.snakes.iter_mut().zip(snakes) {
+                *style = recolor(*style, parse_color(&color)?);
+            }
+        }
+        if let Some(fruits) = self.colors.fruits {
+            for (style, color) in theme.fruits.iter_mut().zip(fruits) {
+                *style = recolor(*style, parse_color(&color)?);
+            }
+        }
+        Ok(theme)
+    }
+}
+
+/// Used to parse the name of a color, or a color written as #rrggbb
+pub fn parse_color(name: &str) -> Result<Color, String> {
+    let color = match name.to_ascii_lowercase().replace(['-', '_', ' '], "").as_str() {
+        "reset" | "none" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "darkgray" | "darkgrey" => Color::DarkGray,
+        "lightred" => Color::LightRed,
+        "lightgreen" => Color::LightGreen,
+        "lightyellow" => Color::LightYellow,
+        "lightblue" => Color::LightBlue,
+        "lightmagenta" => Color::LightMagenta,
+        "lightcyan" => Color::LightCyan,
+        "white" => Color::White,
+        hex if hex.starts_with('#') && hex.len() == 7 && hex.is_ascii() => {
+            let channel = |range| u8::from_str_radix(&hex[range], 16).map_err(|_| format!("invalid color {:?}", name));
+            Color::Rgb(channel(1..3)?, channel(3..5)?, channel(5..7)?)
+        }
+        _ => return Err(format!("invalid color {:?}", name)),
+    };
+    Ok(color)
+}
+
+#[cfg(test)]
+mod theme_test {
+    use crate::theme::*;
+
+    #[test]
+    fn test_builtin() {
+        for name in BUILTIN {
+            assert!(Theme::builtin(name).is_some());
+        }
+        assert!(Theme::builtin("fancy").is_none());
+    }
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(Ok(Color::LightRed), parse_color("light-red"));
+        assert_eq!(Ok(Color::Rgb(255, 0, 16)), parse_color("#ff0010"));
+        assert!(parse_color("#ff00").is_err());
+        assert!(parse_color("#aé123").is_err());
+        assert!(parse_color("purple").is_err());
+    }
+
+    #[test]
+    fn test_from_json() {
+        let theme = Theme::from_json(
+            r##"{"base": "unicode", "glyphs": {"heads": ["OO", "QQ"]}, "colors": {"snakes": ["cyan", "#000000"]}}"##,
+        )
+        .unwrap();
+        assert_eq!(("OO", Style::default().fg(Color::Cyan)), theme.snake(0, 0, &Segment::Head));
+        assert_eq!("━━", theme.snake(1, 1, &Segment::Body(Directions::LEFT, Directions::RIGHT)).0);
+        assert!(Theme::from_json(r#"{"base": "fancy"}"#).is_err());
+        assert!(Theme::from_json(r#"{"glyph": {}}"#).is_err());
+    }
+
+    #[test]
+    fn test_teams() {
+        let theme = Theme::default();
+        let (one, two) = (theme.snake(0, 0, &Segment::Head), theme.snake(0, 1, &Segment::Head));
+        assert_eq!(one.1, two.1);
+        assert_ne!(one.0, two.0);
+        assert_ne!(one.1, theme.snake(1, 1, &Segment::Head).1);
+    }
+
+    #[test]
+    fn test_palette() {
+        let theme = Theme::default().with_palette(&Palette::RedGreen);
+        assert_eq!(Some(Color::Rgb(0, 114, 178)), theme.snake(0, 0, &Segment::Head).1.bg);
+        assert_eq!(Some(Color::Rgb(213, 94, 0)), theme.fruit(1).1.fg);
+
+        let theme = Theme::default().with_palette(&Palette::None);
+        for (_, style) in [theme.cell(&Items::WALL), theme.snake(1, 1, &Segment::Tail), theme.fruit(0)] {
+            assert_eq!((None, None), (style.fg, style.bg));
+        }
+        assert_ne!(theme.snake(0, 0, &Segment::Head).0, theme.snake(1, 1, &Segment::Head).0);
+        assert_ne!(theme.fruit(0).0, theme.fruit(1).0);
+    }
+}
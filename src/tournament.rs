@@ -0,0 +1,360 @@
+use std::{
+    fmt::{self, Display},
+    io,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    bot::{self, Bot, ExternalBot},
+    game::Game,
+    level::Level,
+    snake::Snake,
+    team::Team,
+};
+
+/// The rating every bot starts a tournament with
+const START_ELO: f64 = 1500.0;
+/// How much a single match can change a rating
+const ELO_K: f64 = 32.0;
+
+/// A bot taking part in a tournament
+#[derive(Clone, Debug)]
+pub enum BotSpec {
+    Builtin(String),
+    External(String),
+}
+
+impl BotSpec {
+    /// Parses a bot, which is the name of a built-in bot, or `cmd:` followed by a command
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        if let Some(command) = spec.strip_prefix("cmd:") {
+            if command.trim().is_empty() {
+                return Err("the command of an external bot can not be empty".to_string());
+            }
+            Ok(BotSpec::External(command.to_string()))
+        } else if bot::BUILTIN.contains(&spec) {
+            Ok(BotSpec::Builtin(spec.to_string()))
+        } else {
+            Err(format!(
+                "unknown bot {:?}, expected one of {} or cmd:<command>",
+                spec,
+                bot::BUILTIN.join(", ")
+            ))
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            BotSpec::Builtin(name) => name,
+            BotSpec::External(command) => command,
+        }
+    }
+
+    fn create(&self, seed: u64, timeout: Duration) -> io::Result<Box<dyn Bot + Send>> {
+        match self {
+            BotSpec::Builtin(name) => Ok(bot::builtin(name, seed).unwrap()),
+            BotSpec::External(command) => Ok(Box::new(ExternalBot::spawn(command, timeout)?)),
+        }
+    }
+}
+
+/// A set of headless matches between bots
+pub struct Tournament {
+    bots: Vec<BotSpec>,
+    levels: Vec<Level>,
+    seeds: Vec<u64>,
+    jobs: usize,
+    max_ticks: usize,
+    timeout: Duration,
+}
+
+/// A single match, where first and second are indexes of bots
+#[derive(Clone, Debug)]
+struct Match {
+    first: usize,
+    second: usize,
+    level: usize,
+    seed: u64,
+}
+
+/// How a match ended
+#[derive(Clone, Debug, PartialEq)]
+pub enum Outcome {
+    /// The bot on the given side, 0 or 1, won
+    Win(usize),
+    Draw,
+}
+
+#[derive(Clone, Debug)]
+struct MatchResult {
+    outcome: Outcome,
+    lengths: [usize; 2],
+}
+
+impl Tournament {
+    /// Creates a new [`Tournament`]
+    ///
+    /// Every pair of bots plays on every level with every seed, once from each side
+    /// jobs is the number of matches played at the same time
+    /// max_ticks is how long a match can go on before the longest snake wins
+    pub fn new(
+        bots: Vec<BotSpec>,
+        levels: Vec<Level>,
+        seeds: Vec<u64>,
+        jobs: usize,
+        max_ticks: usize,
+        timeout: Duration,
+    ) -> Self {
+        Tournament {
+            bots,
+            levels,
+            seeds,
+            jobs: jobs.max(1),
+            max_ticks,
+            timeout,
+        }
+    }
+
+    fn schedule(&self) -> Vec<Match> {
+        let mut matches = vec![];
+        for first in 0..self.bots.len() {
+            for second in 0..self.bots.len() {
+                if first == second {
+                    continue;
+                }
+                for level in 0..self.levels.len() {
+                    for seed in &self.seeds {
+                        matches.push(Match {
+                            first,
+                            second,
+                            level,
+                            seed: *seed,
+                        });
+                    }
+                }
+            }
+        }
+        matches
+    }
+
+    /// Plays every match, and returns the standings
+    pub fn run(&self) -> Standings {
+        let matches = self.schedule();
+        let next = AtomicUsize::new(0);
+        let results = Mutex::new(vec![None; matches.len()]);
+
+        thread::scope(|scope| {
+            for _ in 0..self.jobs {
+                scope.spawn(|| loop {
+                    let index = next.fetch_add(1, Ordering::SeqCst);
+                    let Some(game) = matches.get(index) else {
+                        break;
+                    };
+                    let result = self.play(game);
+                    results.lock().unwrap()[index] = Some(result);
+                });
+            }
+        });
+
+        let mut standings = Standings::new(&self.bots);
+        for (game, result) in matches.iter().zip(results.into_inner().unwrap()) {
+            standings.record(game.first, game.second, &result.unwrap());
+        }
+        standings
+    }
+
+    /// Plays a single match, with the same rules as a normal game
+    fn play(&self, game: &Match) -> MatchResult {
+        let level = &self.levels[game.level];
+        let teams = Team::all(true);
+        let players = (0..2)
+            .map(|index| Snake::new(level.spawn_position(index, 2), &teams[index]))
+            .collect();
+        let mut state = Game::seeded(level.board(), players, teams, game.seed);
+
+        let mut bots = vec![];
+        for (side, bot) in [game.first, game.second].iter().enumerate() {
+            match self.bots[*bot].create(game.seed, self.timeout) {
+                Ok(bot) => bots.push(Some(bot)),
+                Err(_) => {
+                    state.disqualify(side);
+                    bots.push(None);
+                }
+            }
+        }
+
+        while state.ticks() < self.max_ticks {
+            for (side, bot) in bots.iter_mut().enumerate() {
+                if let Some(bot) = bot {
+                    match bot.next_move(&state, side) {
+                        Ok(dirr) => state.players_mut()[side].turn(dirr),
+                        Err(_) => state.disqualify(side),
+                    }
+                }
+            }
+            if !state.tick() {
+                break;
+            }
+        }
+
+        let players = state.players();
+        let lengths = [players[0].get_size(), players[1].get_size()];
+        let outcome = match (players[0].is_alive(), players[1].is_alive()) {
+            (true, false) => Outcome::Win(0),
+            (false, true) => Outcome::Win(1),
+            (true, true) if lengths[0] != lengths[1] => {
+                Outcome::Win(if lengths[0] > lengths[1] { 0 } else { 1 })
+            }
+            _ => Outcome::Draw,
+        };
+        MatchResult { outcome, lengths }
+    }
+}
+
+/// The results of a single bot in a tournament
+#[derive(Clone, Debug)]
+pub struct Standing {
+    pub name: String,
+    pub games: usize,
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+    pub total_length: usize,
+    pub elo: f64,
+}
+
+impl Standing {
+    pub fn win_rate(&self) -> f64 {
+        if self.games == 0 {
+            0.0
+        } else {
+            self.wins as f64 / self.games as f64
+        }
+    }
+
+    pub fn average_length(&self) -> f64 {
+        if self.games == 0 {
+            0.0
+        } else {
+            self.total_length as f64 / self.games as f64
+        }
+    }
+}
+
+/// The results of every bot in a tournament
+pub struct Standings {
+    rows: Vec<Standing>,
+}
+
+impl Standings {
+    fn new(bots: &[BotSpec]) -> Self {
+        Standings {
+            rows: bots
+                .iter()
+                .map(|bot| Standing {
+                    name: bot.name().to_string(),
+                    games: 0,
+                    wins: 0,
+                    draws: 0,
+                    losses: 0,
+                    total_length: 0,
+                    elo: START_ELO,
+                })
+                .collect(),
+        }
+    }
+
+    fn record(&mut self, first: usize, second: usize, result: &MatchResult) {
+        let score = match result.outcome {
+            Outcome::Win(0) => 1.0,
+            Outcome::Win(_) => 0.0,
+            Outcome::Draw => 0.5,
+        };
+        let expected = 1.0 / (1.0 + 10f64.powf((self.rows[second].elo - self.rows[first].elo) / 400.0));
+        let change = ELO_K * (score - expected);
+        self.rows[first].elo += change;
+        self.rows[second].elo -= change;
+
+        for (side, bot) in [first, second].into_iter().enumerate() {
+            let row = &mut self.rows[bot];
+            row.games += 1;
+            row.total_length += result.lengths[side];
+            match result.outcome {
+                Outcome::Win(winner) if winner == side => row.wins += 1,
+                Outcome::Win(_) => row.losses += 1,
+                Outcome::Draw => row.draws += 1,
+            }
+        }
+    }
+
+    /// Used to get the standings, best rated first
+    pub fn ranked(&self) -> Vec<&Standing> {
+        let mut rows: Vec<&Standing> = self.rows.iter().collect();
+        rows.sort_by(|a, b| b.elo.total_cmp(&a.elo));
+        rows
+    }
+}
+
+impl Display for Standings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self.rows.iter().map(|row| row.name.len()).max().unwrap_or(0).max(3);
+        writeln!(
+            f,
+            "{:>4}  {:<width$}  {:>5}  {:>4}  {:>5}  {:>6}  {:>6}  {:>10}  {:>6}",
+            "Rank", "Bot", "Games", "Wins", "Draws", "Losses", "Win %", "Avg length", "Elo",
+        )?;
+        for (rank, row) in self.ranked().into_iter().enumerate() {
+            writeln!(
+                f,
+                "{:>4}  {:<width$}  {:>5}  {:>4}  {:>5}  {:>6}  {:>6.1}  {:>10.1}  {:>6.0}",
+                rank + 1,
+                row.name,
+                row.games,
+                row.wins,
+                row.draws,
+                row.losses,
+                row.win_rate() * 100.0,
+                row.average_length(),
+                row.elo,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tournament_test {
+    use crate::tournament::*;
+
+    #[test]
+    fn test_bot_spec() {
+        assert!(matches!(BotSpec::parse("greedy"), Ok(BotSpec::Builtin(_))));
+        assert!(matches!(BotSpec::parse("cmd:python3 bot.py"), Ok(BotSpec::External(_))));
+        assert!(BotSpec::parse("cmd:").is_err());
+        assert!(BotSpec::parse("clever").is_err());
+    }
+
+    #[test]
+    fn test_run() {
+        let tournament = Tournament::new(
+            vec![BotSpec::Builtin("greedy".to_string()), BotSpec::Builtin("random".to_string())],
+            vec![Level::builtin("classic").unwrap()],
+            vec![0, 1],
+            2,
+            200,
+            Duration::from_millis(100),
+        );
+        let standings = tournament.run();
+        let rows = standings.ranked();
+        assert_eq!(2, rows.len());
+        for row in rows {
+            assert_eq!(4, row.games);
+            assert_eq!(4, row.wins + row.draws + row.losses);
+        }
+    }
+}